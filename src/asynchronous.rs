@@ -0,0 +1,219 @@
+//! An async, `Stream`-based client for RIS Live, for callers integrating into a tokio service
+//! rather than driving a blocking read loop themselves.
+
+use crate::client::{ReconnectPolicy, TlsTrust};
+use crate::error::ParserRisliveError;
+use crate::messages::ris_message::RisRrc;
+use crate::messages::{RisLiveClientMessage, RisLiveMessage};
+use crate::{parse_ris_live_message, parse_ris_live_message_with_raw};
+use bgp_models::prelude::BgpElem;
+use futures_util::{SinkExt, Stream, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, connect_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
+
+/// Size of the bounded channel between the producer task and the returned stream; once full,
+/// the producer's `send` awaits, applying backpressure all the way back to the WebSocket read.
+const CHANNEL_CAPACITY: usize = 1024;
+
+async fn connect(
+    url: &str,
+    tls: &TlsTrust,
+) -> tokio_tungstenite::tungstenite::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    match tls {
+        TlsTrust::Default => {
+            let (ws, _response) = connect_async(url).await?;
+            Ok(ws)
+        }
+        TlsTrust::CustomRootCert(path) => {
+            let mut roots = rustls::RootCertStore::empty();
+            roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            let pem = tokio::fs::read(path)
+                .await
+                .map_err(tokio_tungstenite::tungstenite::Error::Io)?;
+            for cert in rustls_pemfile::certs(&mut pem.as_slice()).flatten() {
+                let _ = roots.add(cert);
+            }
+            let tls_config = rustls::ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            let connector = Connector::Rustls(std::sync::Arc::new(tls_config));
+            let (ws, _response) =
+                connect_async_tls_with_config(url, None, false, Some(connector)).await?;
+            Ok(ws)
+        }
+    }
+}
+
+/// Connects to `url`, sends `subscribe_msg`, and returns a stream of parsed [BgpElem]s.
+///
+/// A background task owns the WebSocket and feeds a bounded channel; the returned stream is
+/// just the receiving half, so a slow consumer naturally pushes back on the producer rather
+/// than letting the channel grow without bound.
+pub async fn stream_elems(
+    url: String,
+    subscribe_msg: String,
+    tls: TlsTrust,
+) -> tokio_tungstenite::tungstenite::Result<impl Stream<Item = Result<BgpElem, ParserRisliveError>>>
+{
+    stream_elems_multi(url, vec![subscribe_msg], tls).await
+}
+
+/// Like [stream_elems], but sends several independent subscribe messages over one connection,
+/// so the returned stream carries elements matching any of them.
+///
+/// Uses the default [ReconnectPolicy]; see [stream_elems_multi_with_policy] to customize it.
+pub async fn stream_elems_multi(
+    url: String,
+    subscribe_msgs: Vec<String>,
+    tls: TlsTrust,
+) -> tokio_tungstenite::tungstenite::Result<impl Stream<Item = Result<BgpElem, ParserRisliveError>>>
+{
+    stream_elems_multi_with_policy(url, subscribe_msgs, tls, ReconnectPolicy::default()).await
+}
+
+/// Like [stream_elems_multi], but reconnects and replays every subscribe message sent so far
+/// according to `policy` whenever the WebSocket drops, the same resilience
+/// [crate::client::RisLiveClient] gives the sync API, rather than letting the stream end
+/// silently on the first disconnect.
+pub async fn stream_elems_multi_with_policy(
+    url: String,
+    subscribe_msgs: Vec<String>,
+    tls: TlsTrust,
+    policy: ReconnectPolicy,
+) -> tokio_tungstenite::tungstenite::Result<impl Stream<Item = Result<BgpElem, ParserRisliveError>>>
+{
+    stream_elems_multi_with_policy_and_raw(url, subscribe_msgs, tls, policy, false).await
+}
+
+/// Like [stream_elems_multi], but with the default [ReconnectPolicy] and, when `include_raw` is
+/// set, every element is parsed through [crate::parse_ris_live_message_with_raw] so
+/// `BgpElem::next_hop`/`local_pref`/`atomic`/communities benefit from the raw PDU. Callers must
+/// themselves set
+/// [crate::messages::RisSubscribe::include_raw] on `subscribe_msgs` for the server to actually
+/// send the `raw` field; this only controls which parser the client applies to what comes back.
+pub async fn stream_elems_multi_with_raw(
+    url: String,
+    subscribe_msgs: Vec<String>,
+    tls: TlsTrust,
+    include_raw: bool,
+) -> tokio_tungstenite::tungstenite::Result<impl Stream<Item = Result<BgpElem, ParserRisliveError>>>
+{
+    stream_elems_multi_with_policy_and_raw(url, subscribe_msgs, tls, ReconnectPolicy::default(), include_raw).await
+}
+
+/// Like [stream_elems_multi_with_policy], but also accepts `include_raw`; see
+/// [stream_elems_multi_with_raw].
+pub async fn stream_elems_multi_with_policy_and_raw(
+    url: String,
+    subscribe_msgs: Vec<String>,
+    tls: TlsTrust,
+    policy: ReconnectPolicy,
+    include_raw: bool,
+) -> tokio_tungstenite::tungstenite::Result<impl Stream<Item = Result<BgpElem, ParserRisliveError>>>
+{
+    let mut ws = connect(&url, &tls).await?;
+    for subscribe_msg in &subscribe_msgs {
+        ws.send(Message::Text(subscribe_msg.clone())).await?;
+    }
+
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        let mut attempt = 0u32;
+        loop {
+            let next = ws.next().await;
+            let msg = match next {
+                Some(Ok(msg)) => msg,
+                Some(Err(_)) | None => {
+                    if let Some(max) = policy.max_retries {
+                        if attempt >= max {
+                            return;
+                        }
+                    }
+                    tokio::time::sleep(policy.backoff_for(attempt)).await;
+                    attempt += 1;
+                    match reconnect_and_resubscribe(&url, &tls, &subscribe_msgs).await {
+                        Ok(new_ws) => {
+                            ws = new_ws;
+                            attempt = 0;
+                        }
+                        Err(_) => continue,
+                    }
+                    continue;
+                }
+            };
+            let text = match msg.to_text() {
+                Ok(text) if !text.is_empty() => text,
+                _ => continue,
+            };
+            for result in split_elems(text, include_raw) {
+                if tx.send(result).await.is_err() {
+                    // receiver dropped; stop driving the socket
+                    return;
+                }
+            }
+        }
+    });
+
+    Ok(ReceiverStream::new(rx))
+}
+
+/// Reconnects to `url` and replays every subscription in `subscribe_msgs`, mirroring
+/// [crate::client::RisLiveClient::reconnect] for the async transport.
+async fn reconnect_and_resubscribe(
+    url: &str,
+    tls: &TlsTrust,
+    subscribe_msgs: &[String],
+) -> tokio_tungstenite::tungstenite::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let mut ws = connect(url, tls).await?;
+    for subscribe_msg in subscribe_msgs {
+        ws.send(Message::Text(subscribe_msg.clone())).await?;
+    }
+    Ok(ws)
+}
+
+fn split_elems(text: &str, include_raw: bool) -> Vec<Result<BgpElem, ParserRisliveError>> {
+    let parsed = if include_raw {
+        parse_ris_live_message_with_raw(text)
+    } else {
+        parse_ris_live_message(text)
+    };
+    match parsed {
+        Ok(elems) => elems.into_iter().map(Ok).collect(),
+        // passed through as-is, not swallowed, so callers see the same
+        // `ParserRisliveError::ElemEndOfRibPrefix` diagnostic the firehose path reports.
+        Err(e) => vec![Err(e)],
+    }
+}
+
+/// Queries the RIS Live server for its current list of available route collectors, used to
+/// implement `--list-rrcs`.
+pub async fn request_rrc_list(
+    url: String,
+    tls: TlsTrust,
+) -> Result<Vec<RisRrc>, ParserRisliveError> {
+    let mut ws = connect(&url, &tls)
+        .await
+        .map_err(|e| ParserRisliveError::IncorrectJson(e.to_string()))?;
+    ws.send(Message::Text(RisLiveClientMessage::RrcList.to_json_string()))
+        .await
+        .map_err(|e| ParserRisliveError::IncorrectJson(e.to_string()))?;
+
+    while let Some(next) = ws.next().await {
+        let msg = next.map_err(|e| ParserRisliveError::IncorrectJson(e.to_string()))?;
+        let text = match msg.to_text() {
+            Ok(text) if !text.is_empty() => text,
+            _ => continue,
+        };
+        match serde_json::from_str::<RisLiveMessage>(text) {
+            Ok(RisLiveMessage::RisRrcList(list)) => return Ok(list.rrcs),
+            Ok(RisLiveMessage::RisError(err)) => return Err(ParserRisliveError::ServerError(err.message)),
+            _ => continue,
+        }
+    }
+    Err(ParserRisliveError::IncorrectJson(
+        "connection closed before a ris_rrc_list response arrived".to_string(),
+    ))
+}
@@ -0,0 +1,296 @@
+//! A resilient RIS Live WebSocket client.
+//!
+//! The RIS Live manual notes that if a client can't keep up with the stream, the server sends
+//! a final `ris_error` and closes the connection. [RisLiveClient] wraps the raw WebSocket so
+//! that any disconnect - server-initiated or transport-level - is followed by a reconnect with
+//! exponential backoff, after which every subscription sent so far is replayed so the stream
+//! resumes transparently from the caller's point of view.
+
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{client_tls_with_config, connect, Connector, Message, WebSocket};
+
+use crate::messages::{RisSubscribe, SubscriptionHandle, SubscriptionManager};
+
+/// How to establish the transport-level connection before the WebSocket handshake.
+///
+/// RIS Live is reachable over both `wss://` (the default) and plain `ws://`; this only
+/// affects `wss://` URLs, where it selects between the platform's default TLS trust store and
+/// a caller-supplied one (for clients behind a TLS-inspecting proxy).
+#[derive(Debug, Clone, Default)]
+pub enum TlsTrust {
+    /// Use tungstenite's default connector (the platform/native trust store).
+    #[default]
+    Default,
+    /// Trust the root certificates in this PEM-encoded bundle, in addition to the bundled
+    /// webpki (Mozilla) root set.
+    CustomRootCert(PathBuf),
+}
+
+fn connect_socket(
+    url: &str,
+    tls: &TlsTrust,
+) -> tungstenite::Result<WebSocket<MaybeTlsStream<TcpStream>>> {
+    let ca_path = match tls {
+        TlsTrust::Default => {
+            let (socket, _response) = connect(url)?;
+            return Ok(socket);
+        }
+        TlsTrust::CustomRootCert(path) => path,
+    };
+
+    let request = url.into_client_request()?;
+    let host = request
+        .uri()
+        .host()
+        .expect("RIS Live URL always has a host")
+        .to_string();
+    let default_port = if request.uri().scheme_str() == Some("ws") { 80 } else { 443 };
+    let port = request.uri().port_u16().unwrap_or(default_port);
+    let tcp_stream = TcpStream::connect((host.as_str(), port))?;
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let pem = std::fs::read(ca_path).map_err(tungstenite::Error::Io)?;
+    for cert in rustls_pemfile::certs(&mut pem.as_slice()).flatten() {
+        // ignore certs that don't parse as valid roots; the connection will simply fail
+        // the handshake if the bundle turns out to be unusable.
+        let _ = roots.add(cert);
+    }
+    let tls_config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let connector = Connector::Rustls(Arc::new(tls_config));
+
+    let (socket, _response) = client_tls_with_config(request, tcp_stream, None, Some(connector))?;
+    Ok(socket)
+}
+
+/// Backoff/retry policy used by [RisLiveClient] between reconnect attempts.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound the doubling backoff is capped at.
+    pub max_backoff: Duration,
+    /// Give up after this many consecutive failed attempts. `None` retries forever.
+    pub max_retries: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            max_retries: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Backoff delay for the `attempt`-th consecutive failure (0-indexed), doubling from
+    /// `initial_backoff` up to `max_backoff` with up to 20% of randomized jitter added on top,
+    /// so that many clients disconnected by the same event (e.g. a server restart) don't all
+    /// reconnect in lockstep.
+    pub(crate) fn backoff_for(&self, attempt: u32) -> Duration {
+        let doubled = self.initial_backoff.saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX));
+        let capped = doubled.min(self.max_backoff);
+        let jitter_millis = (rand::random::<f64>() * capped.as_millis() as f64 * 0.2) as u64;
+        capped + Duration::from_millis(jitter_millis)
+    }
+}
+
+/// Error returned once [RisLiveClient] has exhausted its [ReconnectPolicy::max_retries].
+#[derive(Debug)]
+pub struct GiveUpError {
+    pub attempts: u32,
+}
+
+/// A RIS Live WebSocket connection that reconnects itself and replays subscriptions.
+///
+/// Subscriptions are tracked as the raw JSON text sent to the server (built with
+/// [crate::messages::RisSubscribe::to_json_string]), so any subscription representation can be
+/// replayed without this client knowing its shape.
+pub struct RisLiveClient {
+    url: String,
+    policy: ReconnectPolicy,
+    tls: TlsTrust,
+    subscriptions: Vec<String>,
+    filters: SubscriptionManager,
+    socket: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+}
+
+impl RisLiveClient {
+    pub fn new(url: impl Into<String>, policy: ReconnectPolicy) -> Self {
+        Self::with_tls_trust(url, policy, TlsTrust::default())
+    }
+
+    pub fn with_tls_trust(url: impl Into<String>, policy: ReconnectPolicy, tls: TlsTrust) -> Self {
+        RisLiveClient {
+            url: url.into(),
+            policy,
+            tls,
+            subscriptions: vec![],
+            filters: SubscriptionManager::new(),
+            socket: None,
+        }
+    }
+
+    /// Sends a subscription message now, and records it so it is replayed on every future
+    /// reconnect.
+    pub fn subscribe(&mut self, subscribe_msg: String) -> tungstenite::Result<()> {
+        if self.socket.is_none() {
+            self.reconnect()?;
+        }
+        self.send_raw(&subscribe_msg)?;
+        self.subscriptions.push(subscribe_msg);
+        Ok(())
+    }
+
+    /// Adds one filter as its own subscription, independent of any others already active on
+    /// this connection, and returns a handle that can later be used to cancel it with
+    /// [RisLiveClient::unsubscribe].
+    pub fn subscribe_filter(&mut self, filter: RisSubscribe) -> tungstenite::Result<SubscriptionHandle> {
+        if self.socket.is_none() {
+            self.reconnect()?;
+        }
+        // the server only reports the subscription id needed for unsubscribing when asked to
+        let (handle, client_msg) = self.filters.add(filter.acknowledge(true));
+        self.send_raw(&client_msg.to_json_string())?;
+        Ok(handle)
+    }
+
+    /// Cancels the subscription `handle` refers to, if the server has acknowledged it with a
+    /// subscription id. The other active subscriptions on this connection are unaffected.
+    pub fn unsubscribe(&mut self, handle: SubscriptionHandle) -> tungstenite::Result<bool> {
+        let Some(client_msg) = self.filters.unsubscribe(handle) else {
+            return Ok(false);
+        };
+        self.send_raw(&client_msg.to_json_string())?;
+        Ok(true)
+    }
+
+    fn send_raw(&mut self, msg: &str) -> tungstenite::Result<()> {
+        self.socket
+            .as_mut()
+            .expect("socket connected before send_raw is called")
+            .send(Message::Text(msg.to_string()))
+    }
+
+    fn reconnect(&mut self) -> tungstenite::Result<()> {
+        let socket = connect_socket(self.url.as_str(), &self.tls)?;
+        self.socket = Some(socket);
+        for subscribe_msg in self.subscriptions.clone() {
+            self.send_raw(&subscribe_msg)?;
+        }
+        // typed filters lose their server-assigned subscription id across a reconnect, so
+        // they are resent and re-acknowledged against the new connection.
+        for (_handle, client_msg) in self.filters.reset_for_reconnect() {
+            self.send_raw(&client_msg.to_json_string())?;
+        }
+        Ok(())
+    }
+
+    /// Reads one message, transparently reconnecting (and replaying subscriptions) if the
+    /// connection drops, up to [ReconnectPolicy::max_retries] consecutive failures.
+    pub fn read(&mut self) -> Result<String, GiveUpError> {
+        let mut attempt = 0;
+        loop {
+            if self.socket.is_none() && self.reconnect().is_err() {
+                attempt = self.backoff_or_give_up(attempt)?;
+                continue;
+            }
+
+            let read_result = self
+                .socket
+                .as_mut()
+                .expect("just (re)connected above")
+                .read();
+
+            match read_result {
+                Ok(msg) => {
+                    let text = msg.to_string();
+                    self.observe_subscribe_ok(&text);
+                    return Ok(text);
+                }
+                Err(_) => {
+                    self.socket = None;
+                    attempt = self.backoff_or_give_up(attempt)?;
+                }
+            }
+        }
+    }
+
+    /// If `text` is a `ris_subscribe_ok` frame, records its subscription id against the
+    /// oldest not-yet-acknowledged typed filter. The message is still returned to the caller
+    /// as-is; this is bookkeeping only.
+    fn observe_subscribe_ok(&mut self, text: &str) {
+        use crate::messages::RisLiveMessage;
+        if let Ok(RisLiveMessage::RisSubscribeOk(ok)) = serde_json::from_str::<RisLiveMessage>(text) {
+            self.filters.ack_next(ok.subscription);
+        }
+    }
+
+    fn backoff_or_give_up(&self, attempt: u32) -> Result<u32, GiveUpError> {
+        if let Some(max) = self.policy.max_retries {
+            if attempt >= max {
+                return Err(GiveUpError { attempts: attempt });
+            }
+        }
+        sleep(self.policy.backoff_for(attempt));
+        Ok(attempt + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_for_doubles_each_attempt_before_the_cap() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(60),
+            max_retries: None,
+        };
+        // jitter adds up to 20% on top, so compare the undoubled floor of each delay.
+        assert!(policy.backoff_for(0) >= Duration::from_millis(100));
+        assert!(policy.backoff_for(0) < Duration::from_millis(120));
+        assert!(policy.backoff_for(1) >= Duration::from_millis(200));
+        assert!(policy.backoff_for(1) < Duration::from_millis(240));
+        assert!(policy.backoff_for(2) >= Duration::from_millis(400));
+        assert!(policy.backoff_for(2) < Duration::from_millis(480));
+    }
+
+    #[test]
+    fn backoff_for_is_capped_at_max_backoff() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            max_retries: None,
+        };
+        // many doublings would overflow Duration without the cap (and the `checked_shl` guard).
+        let backoff = policy.backoff_for(63);
+        assert!(backoff >= Duration::from_secs(10));
+        assert!(backoff < Duration::from_secs(12));
+    }
+
+    #[test]
+    fn backoff_for_jitter_stays_within_twenty_percent() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(1),
+            max_retries: None,
+        };
+        for _ in 0..100 {
+            let backoff = policy.backoff_for(0);
+            assert!(backoff >= Duration::from_secs(1));
+            assert!(backoff <= Duration::from_millis(1200));
+        }
+    }
+}
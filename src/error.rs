@@ -0,0 +1,50 @@
+//! Error types returned by the RIS Live message parser.
+
+use std::fmt::{Display, Formatter};
+
+/// Errors that can occur while parsing a RIS Live message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParserRisliveError {
+    /// The message is not valid JSON, or does not match the expected schema. Carries the
+    /// original message string for diagnostics.
+    IncorrectJson(String),
+    /// The `announcements`/`withdrawals` array contained the sentinel `"eor"` (end-of-RIB)
+    /// prefix, which is not a real prefix and should be skipped by callers.
+    ElemEndOfRibPrefix,
+    /// The `origin` field held a value other than `igp`, `egp`, or `incomplete`.
+    ElemUnknownOriginType(String),
+    /// The `aggregator` field was not in the expected `asn:ip` form.
+    ElemIncorrectAggregator(String),
+    /// A prefix string in `announcements`/`withdrawals` could not be parsed.
+    ElemIncorrectPrefix(String),
+    /// The server sent a `ris_error` frame, e.g. right before closing the connection because
+    /// the client fell too far behind the stream. Carries the server's message text.
+    ServerError(String),
+}
+
+impl Display for ParserRisliveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParserRisliveError::IncorrectJson(msg) => {
+                write!(f, "incorrect JSON format: {}", msg)
+            }
+            ParserRisliveError::ElemEndOfRibPrefix => {
+                write!(f, "end-of-RIB marker prefix")
+            }
+            ParserRisliveError::ElemUnknownOriginType(origin) => {
+                write!(f, "unknown origin type: {}", origin)
+            }
+            ParserRisliveError::ElemIncorrectAggregator(aggr) => {
+                write!(f, "incorrect aggregator format: {}", aggr)
+            }
+            ParserRisliveError::ElemIncorrectPrefix(prefix) => {
+                write!(f, "incorrect prefix format: {}", prefix)
+            }
+            ParserRisliveError::ServerError(message) => {
+                write!(f, "server closed: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParserRisliveError {}
@@ -55,11 +55,16 @@ fn main() {
 use std::net::IpAddr;
 use bgp_models::prelude::*;
 use crate::error::ParserRisliveError;
+use crate::messages::ris_message::{path_to_as_path, Announcement, RisMessage, RisRrc};
 use crate::messages::{RisLiveMessage, RisMessageEnum};
-use crate::messages::ris_message::path_to_as_path;
 
+pub mod asynchronous;
+pub mod client;
 pub mod error;
 pub mod messages;
+pub mod mrt;
+pub mod raw;
+pub mod transport;
 
 // simple macro to make the code look a bit nicer
 macro_rules! unwrap_or_return {
@@ -71,66 +76,92 @@ macro_rules! unwrap_or_return {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn compose_subscription_message(
-    host: &String,
-    msg_type: &Option<String>,
-    require: &Option<String>,
-    peer: &Option<String>,
-    prefix: &Option<String>,
-    path: &Option<String>,
-    more_specific: &bool,
-    less_specific: &bool,
-) -> String {
-    let mut options: Vec<String> = vec![];
-
-    if host.to_lowercase().as_str() != "all" {
-        options.push(format!("\"host\": \"{}\"", host))
-    }
-
-    if let Some(msg_type) = msg_type {
-        options.push(format!("\"type\": \"{}\"", msg_type))
-    }
-
-    if let Some(require) = require {
-        options.push(format!("\"require\": \"{}\"", require))
-    }
+/// A higher-level view of a RIS Live frame, for consumers that want to react to peering state
+/// and control-frame events rather than only `UPDATE`-derived elements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RisLiveEvent {
+    /// Zero or more [BgpElem]s decoded from an `UPDATE` message.
+    Elems(Vec<BgpElem>),
+    /// A `RIS_PEER_STATE` event: a collector's peering session with `peer` changed state.
+    PeerState {
+        host: String,
+        peer: String,
+        peer_asn: Asn,
+        state: String,
+    },
+    /// An `OPEN` message observed on a peering session.
+    Open {
+        host: String,
+        peer: String,
+        peer_asn: Asn,
+        direction: String,
+        sys_asn: Asn,
+        hold_time: u32,
+        sender_id: String,
+    },
+    /// A `NOTIFICATION` message observed on a peering session: the BGP error that tore it down.
+    Notification {
+        host: String,
+        peer: String,
+        peer_asn: Asn,
+        direction: String,
+        code: u8,
+        subcode: u8,
+    },
+    /// A `KEEPALIVE` observed on a peering session.
+    KeepAlive {
+        host: String,
+        peer: String,
+        peer_asn: Asn,
+    },
+    /// The server's `ris_rrc_list` response.
+    RrcList(Vec<RisRrc>),
+    /// The server's `ris_subscribe_ok` acknowledgement, carrying the subscription id to use
+    /// with `ris_unsubscribe` later.
+    SubscribeOk { subscription: String },
+    /// A keepalive `pong` frame.
+    Pong,
+    /// A `ris_error` frame: the server closed or refused the connection, e.g. a rate limit.
+    Error(String),
+}
 
-    if let Some(peer) = peer {
-        options.push(format!("\"peer\": \"{}\"", peer))
-    }
+/// Parses one RIS Live frame into a [RisLiveEvent], surfacing peering state changes and
+/// control frames that [parse_ris_live_message] discards.
+pub fn parse_ris_live_event(msg_str: &str) -> Result<RisLiveEvent, ParserRisliveError> {
+    parse_ris_live_event_impl(msg_str, false)
+}
 
-    if let Some(prefix) = prefix {
-        options.push(format!("\"prefix\": \"{}\"", prefix))
-    }
+/// Like [parse_ris_live_event], but additionally hex-decodes the `raw` BGP UPDATE PDU (when
+/// present) to backfill `local_pref`, `atomic`, and extended/large communities, which the JSON
+/// summary omits. This costs a full TLV walk of the PDU per message, so it is opt-in.
+pub fn parse_ris_live_event_with_raw(msg_str: &str) -> Result<RisLiveEvent, ParserRisliveError> {
+    parse_ris_live_event_impl(msg_str, true)
+}
 
-    if let Some(path) = path {
-        options.push(format!("\"path\": \"{}\"", path))
-    }
+/// This function parses one message and returns a result of a vector of [BgpElem]s or an error
+pub fn parse_ris_live_message(msg_str: &str) -> Result<Vec<BgpElem>, ParserRisliveError> {
+    elems_from_event(parse_ris_live_event(msg_str)?)
+}
 
-    match more_specific {
-        true => {
-            options.push("\"moreSpecific\": true".to_string())
-        }
-        false => {
-            options.push("\"moreSpecific\": false".to_string())
-        }
-    }
+/// Like [parse_ris_live_message], but additionally hex-decodes the `raw` BGP UPDATE PDU (when
+/// present) to backfill `local_pref`, `atomic`, and extended/large communities, which the JSON
+/// summary omits. This costs a full TLV walk of the PDU per message, so it is opt-in.
+pub fn parse_ris_live_message_with_raw(msg_str: &str) -> Result<Vec<BgpElem>, ParserRisliveError> {
+    elems_from_event(parse_ris_live_event_with_raw(msg_str)?)
+}
 
-    match less_specific {
-        true => {
-            options.push("\"lessSpecific\": true".to_string())
-        }
-        false => {
-            options.push("\"lessSpecific\": false".to_string())
-        }
+/// Extracts the `Elems` case from a [RisLiveEvent] for [parse_ris_live_message]. A `ris_error`
+/// frame surfaces as [ParserRisliveError::ServerError] rather than an empty vector, so callers
+/// (e.g. the CLI) can detect the server closing the connection; other events yield no elements.
+fn elems_from_event(event: RisLiveEvent) -> Result<Vec<BgpElem>, ParserRisliveError> {
+    match event {
+        RisLiveEvent::Elems(elems) => Ok(elems),
+        RisLiveEvent::Error(message) => Err(ParserRisliveError::ServerError(message)),
+        _ => Ok(vec![]),
     }
-
-    format!("{{\"type\": \"ris_subscribe\", \"data\":{{ {} }} }}", options.join(","))
 }
 
-/// This function parses one message and returns a result of a vector of [BgpElem]s or an error
-pub fn parse_ris_live_message(msg_str: &str) -> Result<Vec<BgpElem>, ParserRisliveError> {
+fn parse_ris_live_event_impl(msg_str: &str, decode_raw: bool) -> Result<RisLiveEvent, ParserRisliveError> {
 
     let msg_string = msg_str.to_string();
 
@@ -142,155 +173,218 @@ pub fn parse_ris_live_message(msg_str: &str) -> Result<Vec<BgpElem>, ParserRisli
 
     match msg {
         RisLiveMessage::RisMessage(ris_msg) => {
-            // we currently only handles the `ris_message` data type. other
-            // types provides meta information, but reveals no BGP elements, and
-            // thus for now will be ignored.
+            let Some(inner) = ris_msg.msg.clone() else { return Ok(RisLiveEvent::Elems(vec![])) };
 
-            if ris_msg.msg.is_none() {
-                return Ok(vec![])
+            let host = ris_msg.host.clone();
+            let peer = ris_msg.peer.clone();
+            let peer_asn = Asn::from(unwrap_or_return!(ris_msg.peer_asn.parse::<u32>(), msg_string));
+
+            match inner {
+                update @ RisMessageEnum::UPDATE { .. } => {
+                    let elems = parse_update_elems(&ris_msg, &msg_string, update, decode_raw)?;
+                    Ok(RisLiveEvent::Elems(elems))
+                }
+                RisMessageEnum::OPEN { direction, version: _, sys_asn, hold_time, sender_id, param: _ } => {
+                    Ok(RisLiveEvent::Open { host, peer, peer_asn, direction, sys_asn, hold_time, sender_id })
+                }
+                RisMessageEnum::NOTIFICATION { direction, code, subcode } => {
+                    Ok(RisLiveEvent::Notification { host, peer, peer_asn, direction, code, subcode })
+                }
+                RisMessageEnum::KEEPALIVE {} => Ok(RisLiveEvent::KeepAlive { host, peer, peer_asn }),
+                RisMessageEnum::RIS_PEER_STATE { state } => Ok(RisLiveEvent::PeerState { host, peer, peer_asn, state }),
             }
+        },
+        RisLiveMessage::RisError(err) => Ok(RisLiveEvent::Error(err.message)),
+        RisLiveMessage::RisRrcList(list) => Ok(RisLiveEvent::RrcList(list.rrcs)),
+        RisLiveMessage::RisSubscribeOk(ok) => Ok(RisLiveEvent::SubscribeOk { subscription: ok.subscription }),
+        RisLiveMessage::Pong(_) => Ok(RisLiveEvent::Pong),
+    }
+}
 
-            match ris_msg.msg.unwrap() {
-                RisMessageEnum::UPDATE {
-                    path,
-                    community,
-                    origin,
-                    med,
-                    aggregator,
-                    announcements,
-                } => {
-                    let mut elems: Vec<BgpElem> = vec![];
-
-                    let peer_ip = unwrap_or_return!(ris_msg.peer.parse::<IpAddr>(), msg_string);
-                    let peer_asn = Asn::from(unwrap_or_return!(ris_msg.peer_asn.parse::<u32>(), msg_string));
-
-                    // parse path
-                    let as_path = path.map(path_to_as_path);
-
-                    // parse community
-                    let communities: Option<Vec<MetaCommunity>> = match community {
-                        None => {None}
-                        Some(cs) => {
-                            let mut comms: Vec<MetaCommunity> = vec![];
-                            for c in cs {
-                                comms.push(MetaCommunity::Community(Community::Custom(Asn::from(c.0),c.1)));
-                            }
-                            Some(comms)
-                        }
-                    };
+/// Parses the elements carried by one `UPDATE` message. Split out of
+/// [parse_ris_live_event_impl] since it is by far the largest case.
+fn parse_update_elems(
+    ris_msg: &RisMessage,
+    msg_string: &str,
+    update: RisMessageEnum,
+    decode_raw: bool,
+) -> Result<Vec<BgpElem>, ParserRisliveError> {
+    let RisMessageEnum::UPDATE { path, community, origin, med, aggregator, announcements } = update else {
+        unreachable!("caller only passes the UPDATE variant")
+    };
+
+    let msg_string = msg_string.to_string();
+    let mut elems: Vec<BgpElem> = vec![];
+
+    let peer_ip = unwrap_or_return!(ris_msg.peer.parse::<IpAddr>(), msg_string);
+    let peer_asn = Asn::from(unwrap_or_return!(ris_msg.peer_asn.parse::<u32>(), msg_string));
 
-                    // parse origin
-                    let bgp_origin = match origin {
-                        None => {None}
-                        Some(o) => {
-                            Some(match o.as_str(){
-                                "igp" | "IGP" => Origin::IGP,
-                                "egp" | "EGP" => Origin::EGP,
-                                "incomplete" | "INCOMPLETE" => Origin::INCOMPLETE,
-                                other => {
-                                    return Err(ParserRisliveError::ElemUnknownOriginType(other.to_string()))
-                                }
-                            })
+    // parse path
+    let as_path = path.map(path_to_as_path);
+
+    // parse community
+    let communities: Option<Vec<MetaCommunity>> = match community {
+        None => {None}
+        Some(cs) => {
+            let mut comms: Vec<MetaCommunity> = vec![];
+            for c in cs {
+                comms.push(MetaCommunity::Community(Community::Custom(Asn::from(c.0),c.1)));
+            }
+            Some(comms)
+        }
+    };
+
+    // parse origin
+    let bgp_origin = match origin {
+        None => {None}
+        Some(o) => {
+            Some(match o.as_str(){
+                "igp" | "IGP" => Origin::IGP,
+                "egp" | "EGP" => Origin::EGP,
+                "incomplete" | "INCOMPLETE" => Origin::INCOMPLETE,
+                other => {
+                    return Err(ParserRisliveError::ElemUnknownOriginType(other.to_string()))
+                }
+            })
+        }
+    };
+
+    // parse aggregator
+    let bgp_aggregator = match aggregator{
+        None => {(None, None)}
+        Some(aggr_str) => {
+            let parts = aggr_str.split(':').collect::<Vec<&str>>();
+            if parts.len()!=2 {
+                return Err(ParserRisliveError::ElemIncorrectAggregator(aggr_str))
+            }
+            let asn = Asn::from(unwrap_or_return!(parts[0].to_owned().parse::<u32>(), msg_string));
+            let ip = unwrap_or_return!(parts[1].to_owned().parse::<IpAddr>(), msg_string);
+            (Some(asn), Some(ip))
+        }
+    };
+
+    // decode the raw PDU, if requested, to recover what the JSON summary omits
+    let raw_attrs = if decode_raw {
+        match &ris_msg.raw {
+            Some(raw) => {
+                let announced_network_prefixes: Vec<NetworkPrefix> = announcements
+                    .iter()
+                    .flatten()
+                    .flat_map(|a| a.prefixes.iter())
+                    .filter_map(|p| p.parse::<NetworkPrefix>().ok())
+                    .collect();
+                Some(raw::decode_raw_update(raw, &announced_network_prefixes)?)
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+    let merged_communities = match raw_attrs.as_ref() {
+        None => communities.clone(),
+        Some(raw) if raw.communities.is_empty() => communities.clone(),
+        Some(raw) => {
+            let mut merged = communities.clone().unwrap_or_default();
+            merged.extend(raw.communities.clone());
+            Some(merged)
+        }
+    };
+
+    // parser announcements
+    if let Some(announcements) = announcements {
+        for announcement in announcements {
+            let nexthop = match announcement.next_hop.parse::<IpAddr>(){
+                Ok(a) => {a}
+                Err(_) => {
+                    return Err(ParserRisliveError::IncorrectJson(msg_string))
+                }
+            };
+            for prefix in &announcement.prefixes {
+                let p = match prefix.parse::<NetworkPrefix>(){
+                    Ok(net) => { net }
+                    Err(_) => {
+                        if prefix == "eor" {
+                            return Err(ParserRisliveError::ElemEndOfRibPrefix)
                         }
-                    };
+                        return Err(ParserRisliveError::ElemIncorrectPrefix(prefix.to_string()))
+                    }
+                };
+
+                // the raw PDU's MP_REACH_NLRI carries the actual global IPv6 next hop RIS
+                // Live sometimes reports only as a link-local address in the JSON `next_hop`;
+                // prefer it when we have it.
+                let next_hop = match (p.addr().is_ipv6(), raw_attrs.as_ref().and_then(|a| a.next_hop_v6)) {
+                    (true, Some(v6)) => IpAddr::V6(v6),
+                    _ => nexthop,
+                };
+
+                elems.push(
+                    BgpElem{
+                        timestamp: ris_msg.timestamp,
+                        elem_type: ElemType::ANNOUNCE,
+                        peer_ip,
+                        peer_asn,
+                        prefix: p,
+                        next_hop: Some(next_hop),
+                        as_path: as_path.clone(),
+                        origin_asns: None,
+                        origin: bgp_origin,
+                        local_pref: raw_attrs.as_ref().and_then(|a| a.local_pref),
+                        med,
+                        communities: merged_communities.clone(),
+                        atomic: raw_attrs.as_ref().and_then(|a| a.atomic),
+                        aggr_asn: bgp_aggregator.0,
+                        aggr_ip: bgp_aggregator.1,
+                    }
+                );
+            }
 
-                    // parse aggregator
-                    let bgp_aggregator = match aggregator{
-                        None => {(None, None)}
-                        Some(aggr_str) => {
-                            let parts = aggr_str.split(':').collect::<Vec<&str>>();
-                            if parts.len()!=2 {
-                                return Err(ParserRisliveError::ElemIncorrectAggregator(aggr_str))
+            if let Some(prefixes) = &announcement.withdrawals {
+                for prefix in prefixes {
+                    let p = match prefix.parse::<NetworkPrefix>(){
+                        Ok(net) => { net }
+                        Err(_) => {
+                            if prefix == "eor" {
+                                return Err(ParserRisliveError::ElemEndOfRibPrefix)
                             }
-                            let asn = Asn::from(unwrap_or_return!(parts[0].to_owned().parse::<u32>(), msg_string));
-                            let ip = unwrap_or_return!(parts[1].to_owned().parse::<IpAddr>(), msg_string);
-                            (Some(asn), Some(ip))
+                            return Err(ParserRisliveError::ElemIncorrectPrefix(prefix.to_string()))
                         }
                     };
-
-                    // parser announcements
-                    if let Some(announcements) = announcements {
-                        for announcement in announcements {
-                            let nexthop = match announcement.next_hop.parse::<IpAddr>(){
-                                Ok(a) => {a}
-                                Err(_) => {
-                                    return Err(ParserRisliveError::IncorrectJson(msg_string))
-                                }
-                            };
-                            for prefix in &announcement.prefixes {
-                                let p = match prefix.parse::<NetworkPrefix>(){
-                                    Ok(net) => { net }
-                                    Err(_) => {
-                                        if prefix == "eor" {
-                                            return Err(ParserRisliveError::ElemEndOfRibPrefix)
-                                        }
-                                        return Err(ParserRisliveError::ElemIncorrectPrefix(prefix.to_string()))
-                                    }
-                                };
-
-                                elems.push(
-                                    BgpElem{
-                                        timestamp: ris_msg.timestamp,
-                                        elem_type: ElemType::ANNOUNCE,
-                                        peer_ip,
-                                        peer_asn,
-                                        prefix: p,
-                                        next_hop: Some(nexthop),
-                                        as_path: as_path.clone(),
-                                        origin_asns: None,
-                                        origin: bgp_origin,
-                                        local_pref: None,
-                                        med,
-                                        communities: communities.clone(),
-                                        atomic: None,
-                                        aggr_asn: bgp_aggregator.0,
-                                        aggr_ip: bgp_aggregator.1,
-                                    }
-                                );
-                            }
-
-                            if let Some(prefixes) = &announcement.withdrawals {
-                                for prefix in prefixes {
-                                    let p = match prefix.parse::<NetworkPrefix>(){
-                                        Ok(net) => { net }
-                                        Err(_) => {
-                                            if prefix == "eor" {
-                                                return Err(ParserRisliveError::ElemEndOfRibPrefix)
-                                            }
-                                            return Err(ParserRisliveError::ElemIncorrectPrefix(prefix.to_string()))
-                                        }
-                                    };
-                                    elems.push(
-                                        BgpElem{
-                                            timestamp: ris_msg.timestamp,
-                                            elem_type: ElemType::WITHDRAW,
-                                            peer_ip,
-                                            peer_asn,
-                                            prefix: p,
-                                            next_hop: None,
-                                            as_path: None,
-                                            origin_asns: None,
-                                            origin: None,
-                                            local_pref: None,
-                                            med: None,
-                                            communities: None,
-                                            atomic: None,
-                                            aggr_asn: None,
-                                            aggr_ip: None,
-                                        }
-                                    );
-
-                                }
-                            }
+                    elems.push(
+                        BgpElem{
+                            timestamp: ris_msg.timestamp,
+                            elem_type: ElemType::WITHDRAW,
+                            peer_ip,
+                            peer_asn,
+                            prefix: p,
+                            next_hop: None,
+                            as_path: None,
+                            origin_asns: None,
+                            origin: None,
+                            local_pref: None,
+                            med: None,
+                            communities: None,
+                            atomic: None,
+                            aggr_asn: None,
+                            aggr_ip: None,
                         }
-                    }
+                    );
 
-                    Ok(elems)
                 }
-                _ => Ok(vec![]),
             }
-        },
-        _ => Ok(vec![]),
+        }
+    }
+
+    Ok(elems)
+}
+
+/// Parses a `ris_rrc_list` response into the list of route collectors it reports.
+///
+/// Sent in response to a `{"type": "ris_rrc_list"}` request, used to implement `--list-rrcs`.
+pub fn parse_rrc_list(msg_str: &str) -> Result<Vec<messages::ris_message::RisRrc>, ParserRisliveError> {
+    match serde_json::from_str(msg_str) {
+        Ok(RisLiveMessage::RisRrcList(list)) => Ok(list.rrcs),
+        _ => Err(ParserRisliveError::IncorrectJson(msg_str.to_string())),
     }
 }
 
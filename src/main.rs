@@ -1,30 +1,31 @@
 extern crate core;
 
-use bgpkit_parser::models::ElemType;
-use bgpkit_parser::parse_ris_live_message;
-use bgpkit_parser::rislive::error::ParserRisliveError;
-use bgpkit_parser::rislive::messages::ris_subscribe::RisSubscribeType;
-use bgpkit_parser::rislive::messages::{RisLiveClientMessage, RisSubscribe};
-use ipnet::IpNet;
-use std::net::IpAddr;
-use std::str::FromStr;
+use bgp_models::bgp::{BgpElem, ElemType};
+use futures_util::StreamExt;
+use ris_live_rs::asynchronous::{request_rrc_list, stream_elems_multi_with_raw};
+use ris_live_rs::client::TlsTrust;
+use ris_live_rs::error::ParserRisliveError;
+use ris_live_rs::messages::{RisSubscribe, RisSubscribeType};
+use ris_live_rs::transport::stream_firehose;
+use std::path::PathBuf;
 use structopt::StructOpt;
-use tungstenite::{connect, Message};
 
-const RIS_LIVE_URL_BASE: &str = "ws://ris-live.ripe.net/v1/ws/";
+const RIS_LIVE_HOST_PATH: &str = "ris-live.ripe.net/v1/ws/";
 
 /// ris-live-reader is a simple cli tool that can stream BGP data from RIS-Live project with websocket.
 /// Check out https://ris-live.ripe.net/ for more data source information.
 #[derive(StructOpt, Debug)]
 #[structopt(name = "ris-live-reader")]
 struct Opts {
+
     /// client name to identify the stream
-    #[structopt(long, default_value = "ris-live-rs")]
+    #[structopt(long, default_value="ris-live-rs")]
     client: String,
 
-    /// Filter by RRC host: e.g. rrc01. Use "all" for the firehose.
-    #[structopt(long, default_value = "rrc21")]
-    host: String,
+    /// Filter by RRC host: e.g. rrc01. Use "all" for the firehose. Repeat to subscribe to
+    /// several hosts at once, each as its own independent subscription.
+    #[structopt(long)]
+    host: Vec<String>,
 
     /// Only include messages of a given BGP or RIS type: UPDATE, OPEN, NOTIFICATION, KEEPALIVE, or RIS_PEER_STATE
     #[structopt(long)]
@@ -42,9 +43,10 @@ struct Opts {
     #[structopt(long)]
     peer: Option<String>,
 
-    /// Filter UPDATE messages by prefixes in announcements or withdrawals
+    /// Filter UPDATE messages by prefixes in announcements or withdrawals. Repeat to subscribe
+    /// to several prefixes at once, each as its own independent subscription.
     #[structopt(long)]
-    prefix: Option<String>,
+    prefix: Vec<String>,
 
     /// Match prefixes that are more specific (part of) `prefix`
     #[structopt(long, parse(from_flag = std::ops::Not::not))]
@@ -54,9 +56,10 @@ struct Opts {
     #[structopt(long)]
     less_specific: bool,
 
-    /// ASN or pattern to match against the AS PATH attribute
+    /// ASN or pattern to match against the AS PATH attribute. Repeat to subscribe to several
+    /// path patterns at once, each as its own independent subscription.
     #[structopt(long)]
-    path: Option<String>,
+    path: Vec<String>,
 
     /// Output as JSON objects
     #[structopt(long)]
@@ -69,107 +72,229 @@ struct Opts {
     /// Print out raw message without parsing
     #[structopt(long)]
     raw: bool,
-}
 
-/// This is an example of subscribing to RIS-Live's streaming data.
-///
-/// For more RIS-Live details, check out their documentation at https://ris-live.ripe.net/manual/
-fn main() {
-    let opts: Opts = Opts::from_args();
+    /// Stream the full, unfiltered feed over the plain-HTTPS firehose instead of subscribing
+    /// over the WebSocket. Subscription filters (--host/--prefix/--path/...) do not apply.
+    #[structopt(long)]
+    firehose: bool,
+
+    /// Connect over plain ws:// instead of the default wss://. RIS Live is also reachable
+    /// unencrypted; only use this behind a trusted network.
+    #[structopt(long)]
+    insecure_ws: bool,
 
-    let url = format!("{}?client={}", RIS_LIVE_URL_BASE, opts.client);
-    // connect to RIPE RIS Live websocket server
-    let (mut socket, _response) =
-        connect(url.as_str()).expect("Can't connect to RIS Live websocket server");
+    /// Path to a PEM-encoded root certificate bundle to trust for the wss:// connection, for
+    /// clients sitting behind a TLS-inspecting proxy.
+    #[structopt(long)]
+    tls_ca_cert: Option<PathBuf>,
 
-    let mut subscribe_msg = RisSubscribe::new();
-    if opts.host == "all" {
-        subscribe_msg.host = None;
-    } else {
-        subscribe_msg.host = Some(opts.host.clone());
+    /// Print the list of currently available route collectors and exit, instead of streaming.
+    #[structopt(long)]
+    list_rrcs: bool,
+
+    /// Ask the server to attach the raw BGP PDU to each message, and use it to recover
+    /// LOCAL_PREF, ATOMIC_AGGREGATE, extended/large communities, and the true IPv6 next hop
+    /// that the JSON summary alone doesn't carry. Only applies to the websocket path.
+    #[structopt(long)]
+    include_raw: bool,
+}
+
+/// Whether `elem` passes the `--update-type` filter, if one was given.
+fn passes_update_type(elem: &BgpElem, update_type: &Option<String>) -> bool {
+    let Some(t) = update_type else { return true };
+    match t.to_lowercase().chars().next().unwrap() {
+        'a' => matches!(elem.elem_type, ElemType::ANNOUNCE),
+        'w' => matches!(elem.elem_type, ElemType::WITHDRAW),
+        _ => panic!("the update types can only be announce or withdrawal"),
     }
-    if let Some(msg_type) = &opts.msg_type {
-        subscribe_msg.data_type = match msg_type.as_str() {
-            "UPDATE" => Some(RisSubscribeType::UPDATE),
-            "OPEN" => Some(RisSubscribeType::OPEN),
-            "NOTIFICATION" => Some(RisSubscribeType::NOTIFICATION),
-            "KEEPALIVE" => Some(RisSubscribeType::KEEPALIVE),
-            "RIS_PEER_STATE" => Some(RisSubscribeType::RIS_PEER_STATE),
-            _ => None,
-        };
+}
+
+fn print_elem(e: &BgpElem, json: bool, pretty: bool) {
+    if json {
+        if pretty {
+            println!("{}", serde_json::to_string_pretty(e).unwrap());
+        } else {
+            println!("{}", serde_json::json!(e));
+        }
+    } else {
+        println!("{}", e);
     }
+}
 
-    if let Some(require) = &opts.require {
-        subscribe_msg.require = Some(require.to_string());
+/// Parses a `--msg-type` value (e.g. "update") into the typed [RisSubscribeType] it denotes.
+fn parse_msg_type(msg_type: &str) -> RisSubscribeType {
+    match msg_type.to_uppercase().as_str() {
+        "UPDATE" => RisSubscribeType::UPDATE,
+        "OPEN" => RisSubscribeType::OPEN,
+        "NOTIFICATION" => RisSubscribeType::NOTIFICATION,
+        "KEEPALIVE" => RisSubscribeType::KEEPALIVE,
+        "RIS_PEER_STATE" => RisSubscribeType::RIS_PEER_STATE,
+        other => panic!("unknown message type: {}", other),
     }
-    if let Some(peer) = &opts.peer {
-        subscribe_msg.peer = Some(IpAddr::from_str(peer).unwrap());
+}
+
+/// Builds the [RisSubscribe] filter shared by every subscription this CLI invocation makes,
+/// applying the flags common to all of them (`--msg-type`/`--require`/`--peer`/specificity).
+fn base_subscription(opts: &Opts) -> RisSubscribe {
+    let mut sub = RisSubscribe::new()
+        .more_specific(opts.more_specific)
+        .less_specific(opts.less_specific);
+    if opts.include_raw {
+        sub = sub.include_raw(true);
     }
-    if let Some(prefix) = &opts.prefix {
-        subscribe_msg.prefix = Some(IpNet::from_str(prefix).unwrap());
+    if let Some(msg_type) = &opts.msg_type {
+        sub = sub.data_type(parse_msg_type(msg_type));
     }
-    if let Some(path) = &opts.path {
-        subscribe_msg.path = Some(path.to_string());
+    if let Some(require) = &opts.require {
+        sub = sub.require(require.clone());
     }
-    if opts.more_specific {
-        subscribe_msg.more_specific = Some(true);
+    if let Some(peer) = &opts.peer {
+        sub = sub.peer(peer.parse().expect("--peer must be a valid IP address"));
     }
-    if opts.less_specific {
-        subscribe_msg.less_specific = Some(true);
+    sub
+}
+
+/// Builds the cartesian product of `--host`/`--prefix`/`--path` values into subscriptions: a
+/// single value in two different flags combines into one subscription matching both (e.g.
+/// `--host rrc01 --prefix 203.0.113.0/24` subscribes to that prefix from that host alone), while
+/// repeating the *same* flag still fans out into independent subscriptions, one per value.
+/// Falls back to a single subscription on the default host when none of the three were given.
+fn build_subscriptions(opts: &Opts) -> Vec<String> {
+    if opts.host.is_empty() && opts.prefix.is_empty() && opts.path.is_empty() {
+        return vec![base_subscription(opts).host("rrc21").to_json_string()];
     }
-    socket
-        .send(Message::Text(subscribe_msg.to_json_string()))
-        .unwrap();
-
-    loop {
-        let msg = socket.read().expect("Error reading message").to_string();
-        if msg.is_empty() {
-            continue;
-        }
-        if opts.raw {
-            println!("{}", msg.as_str());
-            continue;
-        }
-        match parse_ris_live_message(msg.as_str()) {
-            Ok(elems) => {
-                for e in elems {
-                    if let Some(t) = &opts.update_type {
-                        match t.to_lowercase().chars().next().unwrap() {
-                            'a' => match e.elem_type {
-                                ElemType::ANNOUNCE => {}
-                                ElemType::WITHDRAW => continue,
-                            },
-                            'w' => match e.elem_type {
-                                ElemType::ANNOUNCE => continue,
-                                ElemType::WITHDRAW => {
-                                    dbg!("withdrawal appeared");
-                                }
-                            },
-                            _ => {
-                                panic!("the update types can only be announce or withdrawal")
-                            }
-                        }
-                    }
 
-                    if opts.json {
-                        if opts.pretty {
-                            println!("{}", serde_json::to_string_pretty(&e).unwrap());
-                        } else {
-                            println!("{}", serde_json::json!(e));
-                        }
-                    } else {
-                        println!("{}", e);
+    let hosts: Vec<Option<&String>> = if opts.host.is_empty() { vec![None] } else { opts.host.iter().map(Some).collect() };
+    let prefixes: Vec<Option<&String>> = if opts.prefix.is_empty() { vec![None] } else { opts.prefix.iter().map(Some).collect() };
+    let paths: Vec<Option<&String>> = if opts.path.is_empty() { vec![None] } else { opts.path.iter().map(Some).collect() };
+
+    let mut msgs = vec![];
+    for host in &hosts {
+        for prefix in &prefixes {
+            for path in &paths {
+                let mut sub = base_subscription(opts);
+                if let Some(host) = host {
+                    // "all" leaves the host unset, matching every collector
+                    if host.as_str() != "all" {
+                        sub = sub.host(host.clone());
                     }
                 }
+                if let Some(prefix) = prefix {
+                    sub = sub.prefix(prefix.parse().expect("--prefix must be a valid IP prefix"));
+                }
+                if let Some(path) = path {
+                    sub = sub.path(path.clone());
+                }
+                msgs.push(sub.to_json_string());
             }
+        }
+    }
+    msgs
+}
+
+/// Runs the WebSocket subscription path: composes one subscribe message per filter, opens the
+/// async stream over all of them, and prints each element that passes the `--update-type`
+/// filter.
+async fn run_websocket(opts: &Opts) {
+    let scheme = if opts.insecure_ws { "ws" } else { "wss" };
+    let url = format!("{}://{}?client={}", scheme, RIS_LIVE_HOST_PATH, opts.client);
+    let tls = match &opts.tls_ca_cert {
+        Some(path) => TlsTrust::CustomRootCert(path.clone()),
+        None => TlsTrust::Default,
+    };
+
+    let msgs = build_subscriptions(opts);
+    for msg in &msgs {
+        println!("{}", msg);
+    }
+
+    let elems = stream_elems_multi_with_raw(url, msgs, tls, opts.include_raw)
+        .await
+        .expect("Can't connect to RIS Live websocket server");
+    tokio::pin!(elems);
+
+    while let Some(result) = elems.next().await {
+        match result {
+            Ok(e) if passes_update_type(&e, &opts.update_type) => print_elem(&e, opts.json, opts.pretty),
+            Ok(_) => continue,
             Err(error) => {
                 if let ParserRisliveError::ElemEndOfRibPrefix = error {
                     println!("{:?}", &error);
-                    println!("{}", msg);
                     continue;
                 }
+                eprintln!("{}", error);
                 break;
             }
         }
     }
 }
+
+/// Runs `--list-rrcs`: queries the server for its route collector list and prints it.
+async fn run_list_rrcs(opts: &Opts) {
+    let scheme = if opts.insecure_ws { "ws" } else { "wss" };
+    let url = format!("{}://{}?client={}", scheme, RIS_LIVE_HOST_PATH, opts.client);
+    let tls = match &opts.tls_ca_cert {
+        Some(path) => TlsTrust::CustomRootCert(path.clone()),
+        None => TlsTrust::Default,
+    };
+
+    match request_rrc_list(url, tls).await {
+        Ok(rrcs) => {
+            for rrc in rrcs {
+                println!("{}\tactivated={}", rrc.name, rrc.activated);
+            }
+        }
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+/// Runs the plain-HTTPS firehose path, reusing the blocking transport on its own thread.
+async fn run_firehose(opts: &Opts) {
+    let client = opts.client.clone();
+    let raw = opts.raw;
+    let update_type = opts.update_type.clone();
+    let json = opts.json;
+    let pretty = opts.pretty;
+
+    tokio::task::spawn_blocking(move || {
+        stream_firehose(&client, |line| {
+            if raw {
+                println!("{}", line);
+                return;
+            }
+            match ris_live_rs::parse_ris_live_message(line) {
+                Ok(elems) => {
+                    for e in elems {
+                        if !passes_update_type(&e, &update_type) {
+                            continue;
+                        }
+                        print_elem(&e, json, pretty);
+                    }
+                }
+                Err(ParserRisliveError::ElemEndOfRibPrefix) => {
+                    println!("{}", line);
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+        })
+        .expect("firehose stream ended unexpectedly");
+    })
+    .await
+    .expect("firehose task panicked");
+}
+
+/// This is an example of subscribing to RIS-Live's streaming data.
+///
+/// For more RIS-Live details, check out their documentation at https://ris-live.ripe.net/manual/
+#[tokio::main]
+async fn main() {
+    let opts: Opts = Opts::from_args();
+
+    if opts.list_rrcs {
+        run_list_rrcs(&opts).await;
+    } else if opts.firehose {
+        run_firehose(&opts).await;
+    } else {
+        run_websocket(&opts).await;
+    }
+}
@@ -0,0 +1,9 @@
+//! Message types exchanged with the RIS Live WebSocket and HTTPS endpoints.
+
+pub mod ris_message;
+pub mod ris_subscribe;
+pub mod subscription;
+
+pub use ris_message::{RisLiveMessage, RisMessageEnum};
+pub use ris_subscribe::{RisLiveClientMessage, RisSubscribe, RisSubscribeType};
+pub use subscription::{SubscriptionHandle, SubscriptionManager};
@@ -0,0 +1,111 @@
+//! Server-to-client message types: the envelope RIS Live wraps every payload in, and the
+//! `UPDATE`/`OPEN`/`NOTIFICATION`/`KEEPALIVE`/`RIS_PEER_STATE` message bodies it can carry.
+
+use bgp_models::prelude::*;
+use serde::Deserialize;
+
+/// The outer envelope every RIS Live WebSocket/firehose frame is wrapped in, keyed by the
+/// top-level `type` field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum RisLiveMessage {
+    #[serde(rename = "ris_message")]
+    RisMessage(RisMessage),
+    #[serde(rename = "ris_error")]
+    RisError(RisError),
+    #[serde(rename = "ris_rrc_list")]
+    RisRrcList(RisRrcList),
+    #[serde(rename = "ris_subscribe_ok")]
+    RisSubscribeOk(RisSubscribeOk),
+    #[serde(rename = "pong")]
+    Pong(serde_json::Value),
+}
+
+/// The `data` object of a `ris_subscribe_ok` frame: the server-assigned id for the
+/// subscription that was just acknowledged, needed later to `ris_unsubscribe` it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RisSubscribeOk {
+    pub subscription: String,
+}
+
+/// The `data` object of a `ris_message` frame: peer/collector metadata plus, if present, one
+/// BGP/RIS event in `msg`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RisMessage {
+    pub timestamp: f64,
+    pub peer: String,
+    pub peer_asn: String,
+    pub id: String,
+    pub host: String,
+    /// Hex-encoded raw BGP PDU, present on `UPDATE` messages.
+    pub raw: Option<String>,
+    #[serde(flatten)]
+    pub msg: Option<RisMessageEnum>,
+}
+
+/// The `data` object of a `ris_error` frame: a human-readable explanation of why the server
+/// closed or refused the connection (e.g. a rate limit).
+#[derive(Debug, Clone, Deserialize)]
+pub struct RisError {
+    pub message: String,
+}
+
+/// The `data` object of a `ris_rrc_list` frame, returned in response to a `ris_rrc_list`
+/// request and listing the currently available route collectors.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RisRrcList {
+    pub rrcs: Vec<RisRrc>,
+}
+
+/// One route collector entry in a [RisRrcList].
+#[derive(Debug, Clone, Deserialize)]
+pub struct RisRrc {
+    pub name: String,
+    pub activated: bool,
+}
+
+/// One BGP UPDATE announcement: a next hop and the prefixes reached through it, plus any
+/// prefixes withdrawn in the same UPDATE.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Announcement {
+    pub next_hop: String,
+    pub prefixes: Vec<String>,
+    pub withdrawals: Option<Vec<String>>,
+}
+
+/// The RIS/BGP event type carried by a `ris_message`, tagged by its own nested `type` field.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+#[allow(non_camel_case_types)]
+pub enum RisMessageEnum {
+    UPDATE {
+        path: Option<Vec<u32>>,
+        community: Option<Vec<(u32, u16)>>,
+        origin: Option<String>,
+        med: Option<u32>,
+        aggregator: Option<String>,
+        announcements: Option<Vec<Announcement>>,
+    },
+    OPEN {
+        direction: String,
+        version: u8,
+        sys_asn: Asn,
+        hold_time: u32,
+        sender_id: String,
+        param: Option<String>,
+    },
+    NOTIFICATION {
+        direction: String,
+        code: u8,
+        subcode: u8,
+    },
+    KEEPALIVE {},
+    RIS_PEER_STATE {
+        state: String,
+    },
+}
+
+/// Converts a flat AS_PATH segment (as RIS Live reports it) into a [bgp_models] `AsPath`.
+pub fn path_to_as_path(path: Vec<u32>) -> AsPath {
+    AsPath::from_sequence(path.into_iter().map(Asn::from))
+}
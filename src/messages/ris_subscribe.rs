@@ -0,0 +1,178 @@
+//! Client-to-server message types: subscribing to and unsubscribing from RIS Live filters.
+
+use ipnet::IpNet;
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// The BGP/RIS message type a subscription can be restricted to via [RisSubscribe::data_type].
+#[derive(Debug, Clone, Copy, Serialize)]
+#[allow(non_camel_case_types)]
+pub enum RisSubscribeType {
+    UPDATE,
+    OPEN,
+    NOTIFICATION,
+    KEEPALIVE,
+    RIS_PEER_STATE,
+}
+
+/// The `socketOptions` object of a `ris_subscribe` message, controlling what RIS Live attaches
+/// to each `ris_message` frame it sends back.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SocketOptions {
+    /// Include the hex-encoded raw BGP PDU as the `raw` field (see [crate::raw]).
+    #[serde(rename = "includeRaw")]
+    pub include_raw: bool,
+}
+
+/// One subscription filter, serialized as the `data` object of a `ris_subscribe` message.
+///
+/// Build one with [RisSubscribe::new] and its fluent setters; unset fields are omitted from
+/// the outgoing JSON rather than sent as `null`, and every field is escaped correctly by
+/// `serde_json` regardless of what characters it contains.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RisSubscribe {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<RisSubscribeType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<IpNet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(rename = "moreSpecific", skip_serializing_if = "Option::is_none")]
+    pub more_specific: Option<bool>,
+    #[serde(rename = "lessSpecific", skip_serializing_if = "Option::is_none")]
+    pub less_specific: Option<bool>,
+    #[serde(rename = "socketOptions", skip_serializing_if = "Option::is_none")]
+    pub socket_options: Option<SocketOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledge: Option<bool>,
+}
+
+impl RisSubscribe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    pub fn data_type(mut self, data_type: RisSubscribeType) -> Self {
+        self.data_type = Some(data_type);
+        self
+    }
+
+    pub fn require(mut self, require: impl Into<String>) -> Self {
+        self.require = Some(require.into());
+        self
+    }
+
+    pub fn peer(mut self, peer: IpAddr) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    pub fn prefix(mut self, prefix: IpNet) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn more_specific(mut self, more_specific: bool) -> Self {
+        self.more_specific = Some(more_specific);
+        self
+    }
+
+    pub fn less_specific(mut self, less_specific: bool) -> Self {
+        self.less_specific = Some(less_specific);
+        self
+    }
+
+    /// Asks the server to include the raw hex-encoded BGP PDU on each matching `ris_message`,
+    /// for use with [crate::parse_ris_live_message_with_raw].
+    pub fn include_raw(mut self, include_raw: bool) -> Self {
+        self.socket_options = Some(SocketOptions { include_raw });
+        self
+    }
+
+    /// Asks the server to acknowledge this subscription with a `ris_subscribe_ok` frame.
+    pub fn acknowledge(mut self, acknowledge: bool) -> Self {
+        self.acknowledge = Some(acknowledge);
+        self
+    }
+
+    /// Serializes this filter as a standalone `ris_subscribe` message, ready to send over the
+    /// WebSocket.
+    pub fn to_json_string(&self) -> String {
+        RisLiveClientMessage::Subscribe(self.clone()).to_json_string()
+    }
+}
+
+/// A message a client may send to the RIS Live WebSocket endpoint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum RisLiveClientMessage {
+    #[serde(rename = "ris_subscribe")]
+    Subscribe(RisSubscribe),
+    #[serde(rename = "ris_unsubscribe")]
+    Unsubscribe { subscription: String },
+    /// Requests the current list of available route collectors, answered with a
+    /// `ris_rrc_list` frame (see [crate::parse_rrc_list]).
+    #[serde(rename = "ris_rrc_list")]
+    RrcList,
+}
+
+impl RisLiveClientMessage {
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).expect("RisLiveClientMessage always serializes to valid JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `require`/`path` value containing a quote must come out correctly escaped instead of
+    /// producing invalid JSON the way the old `format!`-based builder did.
+    #[test]
+    fn to_json_string_escapes_special_characters() {
+        let msg = RisSubscribe::new()
+            .host("rrc01")
+            .require("AS1234\" OR \"1=1")
+            .path("1234 5678")
+            .to_json_string();
+
+        let parsed: serde_json::Value = serde_json::from_str(&msg).expect("must be valid JSON");
+        assert_eq!(parsed["type"], "ris_subscribe");
+        assert_eq!(parsed["data"]["host"], "rrc01");
+        assert_eq!(parsed["data"]["require"], "AS1234\" OR \"1=1");
+        assert_eq!(parsed["data"]["path"], "1234 5678");
+    }
+
+    /// Unset fields are omitted entirely rather than serialized as `null`.
+    #[test]
+    fn to_json_string_omits_unset_fields() {
+        let msg = RisSubscribe::new().host("rrc01").to_json_string();
+        let parsed: serde_json::Value = serde_json::from_str(&msg).unwrap();
+        let data = parsed["data"].as_object().unwrap();
+        assert_eq!(data.len(), 1);
+        assert_eq!(data["host"], "rrc01");
+    }
+
+    #[test]
+    fn include_raw_sets_socket_options() {
+        let msg = RisSubscribe::new().include_raw(true).to_json_string();
+        let parsed: serde_json::Value = serde_json::from_str(&msg).unwrap();
+        assert_eq!(parsed["data"]["socketOptions"]["includeRaw"], true);
+    }
+}
@@ -0,0 +1,150 @@
+//! Tracks multiple concurrent RIS Live subscriptions held over a single connection.
+//!
+//! RIS Live lets one WebSocket hold several independent filters at once, each acknowledged by
+//! the server with a subscription id that is later needed to cancel just that one filter via
+//! `ris_unsubscribe`. [SubscriptionManager] keeps the bookkeeping this requires: which filters
+//! are active (for replay after a reconnect), and which server-assigned id belongs to which
+//! locally-issued [SubscriptionHandle].
+
+use crate::messages::{RisLiveClientMessage, RisSubscribe};
+use std::collections::{HashMap, VecDeque};
+
+/// A local, opaque reference to one subscription, stable for the lifetime of the connection
+/// regardless of whether the server has acknowledged it yet.
+pub type SubscriptionHandle = u64;
+
+/// Tracks the subscriptions sent over one connection and their server-assigned ids.
+#[derive(Debug, Default)]
+pub struct SubscriptionManager {
+    next_handle: SubscriptionHandle,
+    filters: HashMap<SubscriptionHandle, RisSubscribe>,
+    subscription_ids: HashMap<SubscriptionHandle, String>,
+    awaiting_ack: VecDeque<SubscriptionHandle>,
+}
+
+impl SubscriptionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `filter` as a new subscription and returns the handle it can later be
+    /// looked up or cancelled by, together with the client message to send for it.
+    pub fn add(&mut self, filter: RisSubscribe) -> (SubscriptionHandle, RisLiveClientMessage) {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.awaiting_ack.push_back(handle);
+        self.filters.insert(handle, filter.clone());
+        (handle, RisLiveClientMessage::Subscribe(filter))
+    }
+
+    /// Records a `ris_subscribe_ok` confirmation, matching it to the oldest subscription
+    /// still awaiting acknowledgement (RIS Live acknowledges in the order filters were sent).
+    pub fn ack_next(&mut self, subscription_id: String) -> Option<SubscriptionHandle> {
+        let handle = self.awaiting_ack.pop_front()?;
+        self.subscription_ids.insert(handle, subscription_id);
+        Some(handle)
+    }
+
+    /// Builds the `ris_unsubscribe` message that cancels `handle`, and forgets it locally.
+    /// Returns `None` if `handle` is unknown or not yet acknowledged, in which case the filter
+    /// is left active (and still replayed on reconnect) so a caller can retry once the
+    /// `ris_subscribe_ok` for it arrives.
+    pub fn unsubscribe(&mut self, handle: SubscriptionHandle) -> Option<RisLiveClientMessage> {
+        let subscription_id = self.subscription_ids.remove(&handle)?;
+        self.filters.remove(&handle);
+        Some(RisLiveClientMessage::Unsubscribe {
+            subscription: subscription_id,
+        })
+    }
+
+    /// All filters currently believed active, in the order they were added - the same order
+    /// [SubscriptionManager::reset_for_reconnect] replays them in.
+    pub fn active_filters(&self) -> impl Iterator<Item = &RisSubscribe> {
+        let mut handles: Vec<&SubscriptionHandle> = self.filters.keys().collect();
+        handles.sort_unstable();
+        handles.into_iter().map(move |handle| &self.filters[handle])
+    }
+
+    /// Drops every server-assigned subscription id and re-queues all active filters to be
+    /// acknowledged again, keeping their existing handles. Returns the subscribe messages to
+    /// resend, for replaying all filters over a freshly (re)established connection.
+    pub fn reset_for_reconnect(&mut self) -> Vec<(SubscriptionHandle, RisLiveClientMessage)> {
+        self.subscription_ids.clear();
+        self.awaiting_ack.clear();
+        let mut handles: Vec<SubscriptionHandle> = self.filters.keys().copied().collect();
+        handles.sort_unstable();
+        handles
+            .into_iter()
+            .map(|handle| {
+                self.awaiting_ack.push_back(handle);
+                let filter = self.filters[&handle].clone();
+                (handle, RisLiveClientMessage::Subscribe(filter))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(host: &str) -> RisSubscribe {
+        RisSubscribe::new().host(host)
+    }
+
+    #[test]
+    fn ack_next_matches_oldest_awaiting_handle() {
+        let mut mgr = SubscriptionManager::new();
+        let (first, _) = mgr.add(filter("rrc01"));
+        let (second, _) = mgr.add(filter("rrc02"));
+
+        assert_eq!(mgr.ack_next("first-id".to_string()), Some(first));
+        assert_eq!(mgr.ack_next("second-id".to_string()), Some(second));
+        assert_eq!(mgr.ack_next("stray-id".to_string()), None);
+    }
+
+    #[test]
+    fn unsubscribe_acknowledged_handle_sends_message_and_forgets_filter() {
+        let mut mgr = SubscriptionManager::new();
+        let (handle, _) = mgr.add(filter("rrc01"));
+        mgr.ack_next("sub-id".to_string()).unwrap();
+
+        let msg = mgr.unsubscribe(handle).expect("acknowledged handle must unsubscribe");
+        assert!(matches!(
+            msg,
+            RisLiveClientMessage::Unsubscribe { subscription } if subscription == "sub-id"
+        ));
+        assert_eq!(mgr.active_filters().count(), 0);
+    }
+
+    /// Unsubscribing before the `ris_subscribe_ok` arrives must not drop the filter: there is
+    /// no subscription id yet to tell the server to cancel, so the filter has to stay active
+    /// (and keep being replayed on reconnect) until a retry after the ack succeeds.
+    #[test]
+    fn unsubscribe_before_ack_is_a_noop_and_keeps_the_filter_active() {
+        let mut mgr = SubscriptionManager::new();
+        let (handle, _) = mgr.add(filter("rrc01"));
+
+        assert!(mgr.unsubscribe(handle).is_none());
+        assert_eq!(mgr.active_filters().count(), 1);
+
+        // once the (late) ack arrives, the retried unsubscribe must now succeed.
+        mgr.ack_next("sub-id".to_string()).unwrap();
+        assert!(mgr.unsubscribe(handle).is_some());
+        assert_eq!(mgr.active_filters().count(), 0);
+    }
+
+    #[test]
+    fn reset_for_reconnect_requeues_active_filters_for_ack() {
+        let mut mgr = SubscriptionManager::new();
+        let (handle, _) = mgr.add(filter("rrc01"));
+        mgr.ack_next("sub-id".to_string()).unwrap();
+
+        let replayed = mgr.reset_for_reconnect();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].0, handle);
+
+        // the stale subscription id is gone, so the replayed filter must be re-acknowledged.
+        assert_eq!(mgr.ack_next("new-sub-id".to_string()), Some(handle));
+    }
+}
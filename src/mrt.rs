@@ -0,0 +1,400 @@
+//! Encodes [BgpElem]s as MRT `BGP4MP_MESSAGE`/`BGP4MP_MESSAGE_AS4` records, so a live stream can
+//! be archived in the same format as RIPE's historical MRT dumps and replayed through existing
+//! MRT tooling. This is the write-side counterpart to [crate::raw]'s read-side PDU decoding, and
+//! uses the same attribute-type codes.
+
+use crate::error::ParserRisliveError;
+use bgp_models::prelude::*;
+use std::net::IpAddr;
+
+const MRT_TYPE_BGP4MP: u16 = 16;
+const MRT_SUBTYPE_BGP4MP_MESSAGE: u16 = 1;
+const MRT_SUBTYPE_BGP4MP_MESSAGE_AS4: u16 = 4;
+
+const AFI_IPV4: u16 = 1;
+const AFI_IPV6: u16 = 2;
+
+const BGP_MARKER: [u8; 16] = [0xFF; 16];
+const BGP_MESSAGE_TYPE_UPDATE: u8 = 2;
+
+const PATH_ATTR_ORIGIN: u8 = 1;
+const PATH_ATTR_AS_PATH: u8 = 2;
+const PATH_ATTR_NEXT_HOP: u8 = 3;
+const PATH_ATTR_MED: u8 = 4;
+const PATH_ATTR_LOCAL_PREF: u8 = 5;
+const PATH_ATTR_ATOMIC_AGGREGATE: u8 = 6;
+const PATH_ATTR_AGGREGATOR: u8 = 7;
+const PATH_ATTR_COMMUNITY: u8 = 8;
+const PATH_ATTR_MP_REACH_NLRI: u8 = 14;
+const PATH_ATTR_MP_UNREACH_NLRI: u8 = 15;
+const PATH_ATTR_EXTENDED_COMMUNITIES: u8 = 16;
+const PATH_ATTR_LARGE_COMMUNITIES: u8 = 32;
+
+const ATTR_FLAG_TRANSITIVE: u8 = 0x40;
+const ATTR_FLAG_OPTIONAL_TRANSITIVE: u8 = 0xC0;
+
+/// Encodes `elem` as one MRT record: a common header followed by a `BGP4MP_MESSAGE` (or
+/// `_AS4`, once either ASN or any AS_PATH member needs more than 16 bits) body wrapping a
+/// reconstructed single-prefix
+/// BGP UPDATE.
+///
+/// `local_asn`/`local_addr` fill in the BGP4MP header's "local" side, which RIS Live itself
+/// never reports (it only observes a peer's session with a route collector); callers archiving
+/// a stream typically pass the collector's own identity here.
+pub fn encode_elem(
+    elem: &BgpElem,
+    local_asn: Asn,
+    local_addr: IpAddr,
+) -> Result<Vec<u8>, ParserRisliveError> {
+    if (matches!(elem.peer_ip, IpAddr::V4(_))) != matches!(local_addr, IpAddr::V4(_)) {
+        return Err(ParserRisliveError::IncorrectJson(
+            "peer and local address families must match".to_string(),
+        ));
+    }
+
+    let use_as4 = elem.peer_asn.asn() > u16::MAX as u32
+        || local_asn.asn() > u16::MAX as u32
+        || elem
+            .as_path
+            .as_ref()
+            .map(as_path_needs_as4)
+            .unwrap_or(false);
+
+    let bgp_message = encode_bgp_update(elem, use_as4)?;
+
+    let mut body = vec![];
+    if use_as4 {
+        body.extend_from_slice(&elem.peer_asn.asn().to_be_bytes());
+        body.extend_from_slice(&local_asn.asn().to_be_bytes());
+    } else {
+        body.extend_from_slice(&(elem.peer_asn.asn() as u16).to_be_bytes());
+        body.extend_from_slice(&(local_asn.asn() as u16).to_be_bytes());
+    }
+    body.extend_from_slice(&0u16.to_be_bytes()); // interface index: not meaningful for a live feed
+
+    match (elem.peer_ip, local_addr) {
+        (IpAddr::V4(peer), IpAddr::V4(local)) => {
+            body.extend_from_slice(&AFI_IPV4.to_be_bytes());
+            body.extend_from_slice(&peer.octets());
+            body.extend_from_slice(&local.octets());
+        }
+        (IpAddr::V6(peer), IpAddr::V6(local)) => {
+            body.extend_from_slice(&AFI_IPV6.to_be_bytes());
+            body.extend_from_slice(&peer.octets());
+            body.extend_from_slice(&local.octets());
+        }
+        _ => unreachable!("address family mismatch already checked above"),
+    }
+
+    body.extend_from_slice(&bgp_message);
+
+    let subtype = if use_as4 {
+        MRT_SUBTYPE_BGP4MP_MESSAGE_AS4
+    } else {
+        MRT_SUBTYPE_BGP4MP_MESSAGE
+    };
+
+    let mut record = Vec::with_capacity(12 + body.len());
+    record.extend_from_slice(&(elem.timestamp as u32).to_be_bytes());
+    record.extend_from_slice(&MRT_TYPE_BGP4MP.to_be_bytes());
+    record.extend_from_slice(&subtype.to_be_bytes());
+    record.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    record.extend_from_slice(&body);
+    Ok(record)
+}
+
+/// Encodes every element in `elems` and concatenates the resulting MRT records, for archiving a
+/// batch (e.g. everything decoded from one RIS Live frame) in one write.
+pub fn encode_elems(
+    elems: &[BgpElem],
+    local_asn: Asn,
+    local_addr: IpAddr,
+) -> Result<Vec<u8>, ParserRisliveError> {
+    let mut out = vec![];
+    for elem in elems {
+        out.extend(encode_elem(elem, local_asn, local_addr)?);
+    }
+    Ok(out)
+}
+
+fn as_path_needs_as4(path: &AsPath) -> bool {
+    path.segments.iter().any(|segment| {
+        let asns = match segment {
+            AsPathSegment::AsSequence(asns)
+            | AsPathSegment::AsSet(asns)
+            | AsPathSegment::ConfedSequence(asns)
+            | AsPathSegment::ConfedSet(asns) => asns,
+        };
+        asns.iter().any(|asn| asn.asn() > u16::MAX as u32)
+    })
+}
+
+/// Builds the BGP message bytes (marker, length, type, and the UPDATE body) for `elem`.
+fn encode_bgp_update(elem: &BgpElem, use_as4: bool) -> Result<Vec<u8>, ParserRisliveError> {
+    let mut body = vec![];
+
+    match elem.elem_type {
+        ElemType::WITHDRAW if elem.prefix.addr().is_ipv4() => {
+            let withdrawn = encode_prefix(&elem.prefix)?;
+            body.extend_from_slice(&(withdrawn.len() as u16).to_be_bytes());
+            body.extend_from_slice(&withdrawn);
+            body.extend_from_slice(&0u16.to_be_bytes()); // total path attribute length
+        }
+        ElemType::WITHDRAW => {
+            // the classic Withdrawn Routes field is IPv4-only (RFC 4271); IPv6 withdrawals
+            // instead travel as an MP_UNREACH_NLRI path attribute (RFC 4760).
+            body.extend_from_slice(&0u16.to_be_bytes()); // withdrawn routes length
+            let mut value = vec![0u8, 2, 1]; // AFI = IPv6, SAFI = unicast
+            value.extend_from_slice(&encode_prefix(&elem.prefix)?);
+            let mut attrs = vec![];
+            push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_MP_UNREACH_NLRI, &value);
+            body.extend_from_slice(&(attrs.len() as u16).to_be_bytes());
+            body.extend_from_slice(&attrs);
+        }
+        ElemType::ANNOUNCE => {
+            body.extend_from_slice(&0u16.to_be_bytes()); // withdrawn routes length
+            let attrs = encode_path_attributes(elem, use_as4)?;
+            body.extend_from_slice(&(attrs.len() as u16).to_be_bytes());
+            body.extend_from_slice(&attrs);
+            // IPv4 NLRI goes directly in the UPDATE body; IPv6 NLRI already went into
+            // MP_REACH_NLRI above.
+            if elem.prefix.addr().is_ipv4() {
+                body.extend_from_slice(&encode_prefix(&elem.prefix)?);
+            }
+        }
+    }
+
+    let mut message = Vec::with_capacity(19 + body.len());
+    message.extend_from_slice(&BGP_MARKER);
+    message.extend_from_slice(&((19 + body.len()) as u16).to_be_bytes());
+    message.push(BGP_MESSAGE_TYPE_UPDATE);
+    message.extend_from_slice(&body);
+    Ok(message)
+}
+
+fn encode_path_attributes(elem: &BgpElem, use_as4: bool) -> Result<Vec<u8>, ParserRisliveError> {
+    let mut attrs = vec![];
+
+    if let Some(origin) = elem.origin {
+        let value = match origin {
+            Origin::IGP => 0u8,
+            Origin::EGP => 1u8,
+            Origin::INCOMPLETE => 2u8,
+        };
+        push_attr(&mut attrs, ATTR_FLAG_TRANSITIVE, PATH_ATTR_ORIGIN, &[value]);
+    }
+
+    if let Some(as_path) = &elem.as_path {
+        push_attr(&mut attrs, ATTR_FLAG_TRANSITIVE, PATH_ATTR_AS_PATH, &encode_as_path(as_path, use_as4));
+    }
+
+    match (elem.next_hop, elem.prefix.addr().is_ipv4()) {
+        (Some(IpAddr::V4(next_hop)), true) => {
+            push_attr(&mut attrs, ATTR_FLAG_TRANSITIVE, PATH_ATTR_NEXT_HOP, &next_hop.octets());
+        }
+        (Some(IpAddr::V6(next_hop)), false) => {
+            // IPv6 reachability, including the next hop, travels in MP_REACH_NLRI instead of a
+            // plain NEXT_HOP attribute.
+            let mut value = vec![0u8, 2]; // AFI = IPv6
+            value.push(1); // SAFI = unicast
+            value.push(16); // next hop length
+            value.extend_from_slice(&next_hop.octets());
+            value.push(0); // reserved (SNPA count)
+            value.extend_from_slice(&encode_prefix(&elem.prefix)?);
+            push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_MP_REACH_NLRI, &value);
+        }
+        _ => {
+            return Err(ParserRisliveError::IncorrectJson(
+                "next hop address family does not match prefix address family".to_string(),
+            ))
+        }
+    }
+
+    if let Some(med) = elem.med {
+        push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_MED, &med.to_be_bytes());
+    }
+
+    if let Some(local_pref) = elem.local_pref {
+        push_attr(&mut attrs, ATTR_FLAG_TRANSITIVE, PATH_ATTR_LOCAL_PREF, &local_pref.to_be_bytes());
+    }
+
+    if elem.atomic.unwrap_or(false) {
+        push_attr(&mut attrs, ATTR_FLAG_TRANSITIVE, PATH_ATTR_ATOMIC_AGGREGATE, &[]);
+    }
+
+    if let (Some(asn), Some(IpAddr::V4(ip))) = (elem.aggr_asn, elem.aggr_ip) {
+        let mut value = vec![];
+        if use_as4 {
+            value.extend_from_slice(&asn.asn().to_be_bytes());
+        } else {
+            value.extend_from_slice(&(asn.asn() as u16).to_be_bytes());
+        }
+        value.extend_from_slice(&ip.octets());
+        push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_AGGREGATOR, &value);
+    }
+
+    if let Some(communities) = &elem.communities {
+        let mut plain = vec![];
+        let mut extended = vec![];
+        let mut large = vec![];
+        for community in communities {
+            match community {
+                MetaCommunity::Community(Community::Custom(asn, value)) => {
+                    plain.extend_from_slice(&(asn.asn() as u16).to_be_bytes());
+                    plain.extend_from_slice(&value.to_be_bytes());
+                }
+                MetaCommunity::Community(_) => {
+                    // well-known communities (NO_EXPORT and friends) round-trip through their
+                    // reserved numeric value, which this crate does not currently decode raw
+                    // PDUs into; skip rather than guess at the wrong one.
+                }
+                MetaCommunity::ExtendedCommunity(bytes) => {
+                    extended.extend_from_slice(bytes);
+                }
+                MetaCommunity::LargeCommunity(lc) => {
+                    large.extend_from_slice(&lc.global_administrator.asn().to_be_bytes());
+                    large.extend_from_slice(&lc.local_data[0].to_be_bytes());
+                    large.extend_from_slice(&lc.local_data[1].to_be_bytes());
+                }
+            }
+        }
+        if !plain.is_empty() {
+            push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_COMMUNITY, &plain);
+        }
+        if !extended.is_empty() {
+            push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_EXTENDED_COMMUNITIES, &extended);
+        }
+        if !large.is_empty() {
+            push_attr(&mut attrs, ATTR_FLAG_OPTIONAL_TRANSITIVE, PATH_ATTR_LARGE_COMMUNITIES, &large);
+        }
+    }
+
+    Ok(attrs)
+}
+
+/// Appends one path-attribute TLV to `attrs`, using the 1-byte length form below 256 bytes and
+/// the extended 2-byte form (flag bit 0x10) above it.
+fn push_attr(attrs: &mut Vec<u8>, flags: u8, attr_type: u8, value: &[u8]) {
+    if value.len() > u8::MAX as usize {
+        attrs.push(flags | 0x10);
+        attrs.push(attr_type);
+        attrs.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    } else {
+        attrs.push(flags);
+        attrs.push(attr_type);
+        attrs.push(value.len() as u8);
+    }
+    attrs.extend_from_slice(value);
+}
+
+fn encode_as_path(as_path: &AsPath, use_as4: bool) -> Vec<u8> {
+    let mut out = vec![];
+    for segment in &as_path.segments {
+        let (segment_type, asns): (u8, &Vec<Asn>) = match segment {
+            AsPathSegment::AsSequence(asns) => (2, asns),
+            AsPathSegment::AsSet(asns) => (1, asns),
+            AsPathSegment::ConfedSequence(asns) => (4, asns),
+            AsPathSegment::ConfedSet(asns) => (3, asns),
+        };
+        out.push(segment_type);
+        out.push(asns.len() as u8);
+        for asn in asns {
+            if use_as4 {
+                out.extend_from_slice(&asn.asn().to_be_bytes());
+            } else {
+                out.extend_from_slice(&(asn.asn() as u16).to_be_bytes());
+            }
+        }
+    }
+    out
+}
+
+/// Encodes a prefix as a withdrawn-routes/NLRI entry: a 1-byte prefix length followed by the
+/// minimal number of address bytes it covers.
+fn encode_prefix(prefix: &NetworkPrefix) -> Result<Vec<u8>, ParserRisliveError> {
+    let prefix_len = prefix.prefix_len();
+    let byte_len = (prefix_len as usize).div_ceil(8);
+    let mut out = vec![prefix_len];
+    match prefix.addr() {
+        IpAddr::V4(addr) => out.extend_from_slice(&addr.octets()[..byte_len]),
+        IpAddr::V6(addr) => out.extend_from_slice(&addr.octets()[..byte_len]),
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::decode_raw_update;
+
+    fn announce_elem() -> BgpElem {
+        BgpElem {
+            timestamp: 1636247118.76,
+            elem_type: ElemType::ANNOUNCE,
+            peer_ip: "2001:7f8:24::82".parse().unwrap(),
+            peer_asn: Asn::from(58299u32),
+            prefix: "2602:fd9e:f00::/40".parse().unwrap(),
+            next_hop: Some("2001:7f8:24::82".parse().unwrap()),
+            as_path: Some(AsPath::from_sequence([Asn::from(58299u32), Asn::from(49981u32)])),
+            origin_asns: None,
+            origin: Some(Origin::IGP),
+            local_pref: Some(100),
+            med: None,
+            communities: Some(vec![
+                MetaCommunity::ExtendedCommunity([0, 2, 0, 0, 0xE3, 0xBB, 0, 1]),
+                MetaCommunity::LargeCommunity(LargeCommunity {
+                    global_administrator: Asn::from(58299u32),
+                    local_data: [1, 2],
+                }),
+            ]),
+            atomic: Some(true),
+            aggr_asn: None,
+            aggr_ip: None,
+        }
+    }
+
+    /// Encoding an IPv6 ANNOUNCE and decoding the resulting BGP message back through
+    /// [crate::raw::decode_raw_update] must recover the same next hop and attributes that went
+    /// in, proving `encode_path_attributes`'s MP_REACH_NLRI layout agrees with the read side.
+    #[test]
+    fn encode_bgp_update_ipv6_announce_round_trips_through_raw_decoder() {
+        let elem = announce_elem();
+        let message = encode_bgp_update(&elem, false).unwrap();
+        let raw_hex = message.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+
+        let attrs = decode_raw_update(&raw_hex, &[elem.prefix]).unwrap();
+        assert_eq!(attrs.next_hop_v6, Some("2001:7f8:24::82".parse().unwrap()));
+        assert_eq!(attrs.local_pref, Some(100));
+        assert_eq!(attrs.atomic, Some(true));
+        assert_eq!(attrs.communities, elem.communities.unwrap());
+    }
+
+    /// An IPv6 WITHDRAW must leave the classic (IPv4-only) Withdrawn Routes field empty and
+    /// carry the prefix via an MP_UNREACH_NLRI path attribute instead.
+    #[test]
+    fn encode_bgp_update_ipv6_withdraw_uses_mp_unreach_nlri() {
+        let elem = BgpElem {
+            elem_type: ElemType::WITHDRAW,
+            next_hop: None,
+            as_path: None,
+            origin: None,
+            local_pref: None,
+            communities: None,
+            atomic: None,
+            ..announce_elem()
+        };
+        let message = encode_bgp_update(&elem, false).unwrap();
+
+        let withdrawn_len = u16::from_be_bytes([message[19], message[20]]);
+        assert_eq!(withdrawn_len, 0, "IPv6 withdrawals must not use the IPv4-only field");
+
+        let attrs_start = 21;
+        let attrs_len = u16::from_be_bytes([message[attrs_start], message[attrs_start + 1]]) as usize;
+        let attrs = &message[attrs_start + 2..attrs_start + 2 + attrs_len];
+        assert_eq!(attrs[1], PATH_ATTR_MP_UNREACH_NLRI);
+        let value = &attrs[3..];
+        assert_eq!(&value[0..3], &[0, 2, 1]); // AFI = IPv6, SAFI = unicast
+        assert_eq!(value[3], 40); // prefix length for 2602:fd9e:f00::/40
+        assert_eq!(&value[4..9], &[0x26, 0x02, 0xFD, 0x9E, 0x0F]);
+    }
+}
@@ -0,0 +1,234 @@
+//! Decodes the hex-encoded raw BGP UPDATE PDU that RIS Live includes alongside its JSON
+//! summary, recovering attributes the JSON omits: `LOCAL_PREF`, `ATOMIC_AGGREGATE`, and the
+//! extended/large community lists. Modeled on the same attribute-TLV walk used to decode
+//! UPDATE messages from MRT dumps.
+
+use crate::error::ParserRisliveError;
+use bgp_models::prelude::*;
+use std::net::Ipv6Addr;
+
+const BGP_MARKER_LEN: usize = 16;
+/// Marker + 2-byte total length + 1-byte type, before the UPDATE-specific body starts.
+const BGP_HEADER_LEN: usize = BGP_MARKER_LEN + 3;
+const BGP_MESSAGE_TYPE_UPDATE: u8 = 2;
+
+const PATH_ATTR_LOCAL_PREF: u8 = 5;
+const PATH_ATTR_ATOMIC_AGGREGATE: u8 = 6;
+const PATH_ATTR_MP_REACH_NLRI: u8 = 14;
+const PATH_ATTR_EXTENDED_COMMUNITIES: u8 = 16;
+const PATH_ATTR_LARGE_COMMUNITIES: u8 = 32;
+
+const EXTENDED_LENGTH_FLAG: u8 = 0x10;
+
+/// Attributes recovered from a raw PDU that RIS Live's JSON summary does not carry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawAttributes {
+    pub local_pref: Option<u32>,
+    pub atomic: Option<bool>,
+    pub communities: Vec<MetaCommunity>,
+    /// IPv6 next hop recovered from MP_REACH_NLRI, if the UPDATE carried one.
+    pub next_hop_v6: Option<Ipv6Addr>,
+}
+
+/// Hex-decodes `raw` and walks its path-attributes section, returning the attributes it adds
+/// on top of the JSON summary.
+///
+/// `announced_prefixes` is the set of prefixes RIS Live's JSON `announcements` reported for
+/// this UPDATE; if the PDU's own NLRI disagrees, this returns
+/// [ParserRisliveError::IncorrectJson] instead of silently returning attributes that describe
+/// a different route than the one the JSON described.
+pub fn decode_raw_update(
+    raw: &str,
+    announced_prefixes: &[NetworkPrefix],
+) -> Result<RawAttributes, ParserRisliveError> {
+    let bytes = hex_decode(raw)?;
+    if bytes.len() < BGP_HEADER_LEN || bytes[BGP_MARKER_LEN + 2] != BGP_MESSAGE_TYPE_UPDATE {
+        return Err(ParserRisliveError::IncorrectJson(raw.to_string()));
+    }
+
+    let mut pos = BGP_HEADER_LEN;
+    let withdrawn_len = read_u16(&bytes, pos, raw)? as usize;
+    pos += 2 + withdrawn_len;
+
+    let attrs_len = read_u16(&bytes, pos, raw)? as usize;
+    pos += 2;
+    let attrs_end = pos + attrs_len;
+    if attrs_end > bytes.len() {
+        return Err(ParserRisliveError::IncorrectJson(raw.to_string()));
+    }
+
+    let mut result = RawAttributes::default();
+    let mut nlri_prefixes: Vec<NetworkPrefix> = vec![];
+
+    while pos < attrs_end {
+        let flags = *bytes.get(pos).ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))?;
+        let attr_type = *bytes.get(pos + 1).ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))?;
+        let (len, header_len) = if flags & EXTENDED_LENGTH_FLAG != 0 {
+            (read_u16(&bytes, pos + 2, raw)? as usize, 4)
+        } else {
+            (*bytes.get(pos + 2).ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))? as usize, 3)
+        };
+        let value_start = pos + header_len;
+        let value_end = value_start + len;
+        let value = bytes
+            .get(value_start..value_end)
+            .ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))?;
+
+        match attr_type {
+            PATH_ATTR_LOCAL_PREF if value.len() == 4 => {
+                result.local_pref = Some(u32::from_be_bytes(value.try_into().unwrap()));
+            }
+            PATH_ATTR_ATOMIC_AGGREGATE => {
+                result.atomic = Some(true);
+            }
+            PATH_ATTR_EXTENDED_COMMUNITIES => {
+                for member in value.chunks_exact(8) {
+                    result
+                        .communities
+                        .push(MetaCommunity::ExtendedCommunity(member.try_into().unwrap()));
+                }
+            }
+            PATH_ATTR_LARGE_COMMUNITIES => {
+                for member in value.chunks_exact(12) {
+                    let global_administrator = u32::from_be_bytes(member[0..4].try_into().unwrap());
+                    let local_data_1 = u32::from_be_bytes(member[4..8].try_into().unwrap());
+                    let local_data_2 = u32::from_be_bytes(member[8..12].try_into().unwrap());
+                    result.communities.push(MetaCommunity::LargeCommunity(LargeCommunity {
+                        global_administrator: Asn::from(global_administrator),
+                        local_data: [local_data_1, local_data_2],
+                    }));
+                }
+            }
+            PATH_ATTR_MP_REACH_NLRI => {
+                if let Some(next_hop) = decode_mp_reach_next_hop(value) {
+                    result.next_hop_v6 = Some(next_hop);
+                }
+                nlri_prefixes.extend(decode_mp_reach_nlri(value));
+            }
+            _ => {}
+        }
+
+        pos = value_end;
+    }
+
+    // the trailing NLRI (after the path attributes) carries the IPv4 prefixes this UPDATE
+    // announces; cross-check them against the JSON so a raw-decode bug doesn't silently
+    // attach attributes to the wrong prefix.
+    nlri_prefixes.extend(decode_nlri(&bytes[attrs_end..], raw)?);
+    for prefix in &nlri_prefixes {
+        if !announced_prefixes.is_empty() && !announced_prefixes.contains(prefix) {
+            return Err(ParserRisliveError::IncorrectJson(raw.to_string()));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Decodes the IPv4 NLRI trailing a BGP UPDATE: a sequence of (prefix-length, prefix-bytes).
+fn decode_nlri(bytes: &[u8], raw: &str) -> Result<Vec<NetworkPrefix>, ParserRisliveError> {
+    let mut prefixes = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let prefix_bits = bytes[pos] as usize;
+        let prefix_bytes = prefix_bits.div_ceil(8);
+        pos += 1;
+        let value = bytes
+            .get(pos..pos + prefix_bytes)
+            .ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))?;
+        let mut octets = [0u8; 4];
+        octets[..value.len()].copy_from_slice(value);
+        let addr = std::net::Ipv4Addr::from(octets);
+        if let Ok(net) = format!("{}/{}", addr, prefix_bits).parse::<NetworkPrefix>() {
+            prefixes.push(net);
+        }
+        pos += prefix_bytes;
+    }
+    Ok(prefixes)
+}
+
+/// Extracts the IPv6 next hop from an MP_REACH_NLRI attribute value, if present.
+///
+/// Layout per RFC 4760: AFI(2) + SAFI(1) + Next Hop Length(1) + Next Hop + ...
+fn decode_mp_reach_next_hop(value: &[u8]) -> Option<Ipv6Addr> {
+    let next_hop_len = *value.get(3)? as usize;
+    if next_hop_len < 16 {
+        return None;
+    }
+    let octets: [u8; 16] = value.get(4..20)?.try_into().ok()?;
+    Some(Ipv6Addr::from(octets))
+}
+
+/// Extracts the IPv6 reachable-NLRI prefixes from an MP_REACH_NLRI attribute value.
+fn decode_mp_reach_nlri(value: &[u8]) -> Vec<NetworkPrefix> {
+    let Some(&next_hop_len) = value.get(3) else { return vec![] };
+    let nlri_start = 4 + next_hop_len as usize + 1; // + 1 for the reserved SNPA-count byte
+    let Some(nlri) = value.get(nlri_start..) else { return vec![] };
+
+    let mut prefixes = vec![];
+    let mut pos = 0;
+    while pos < nlri.len() {
+        let prefix_bits = nlri[pos] as usize;
+        let prefix_bytes = prefix_bits.div_ceil(8);
+        pos += 1;
+        let Some(bits) = nlri.get(pos..pos + prefix_bytes) else { break };
+        let mut octets = [0u8; 16];
+        octets[..bits.len()].copy_from_slice(bits);
+        let addr = Ipv6Addr::from(octets);
+        if let Ok(net) = format!("{}/{}", addr, prefix_bits).parse::<NetworkPrefix>() {
+            prefixes.push(net);
+        }
+        pos += prefix_bytes;
+    }
+    prefixes
+}
+
+fn hex_decode(raw: &str) -> Result<Vec<u8>, ParserRisliveError> {
+    if raw.len() % 2 != 0 {
+        return Err(ParserRisliveError::IncorrectJson(raw.to_string()));
+    }
+    (0..raw.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&raw[i..i + 2], 16)
+                .map_err(|_| ParserRisliveError::IncorrectJson(raw.to_string()))
+        })
+        .collect()
+}
+
+fn read_u16(bytes: &[u8], pos: usize, raw: &str) -> Result<u16, ParserRisliveError> {
+    bytes
+        .get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| ParserRisliveError::IncorrectJson(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw PDU from `lib.rs`'s `test_ris_live_msg` fixture: a real IPv6 UPDATE carrying a
+    /// 32-byte (global + link-local) MP_REACH_NLRI next hop and a single `2602:fd9e:f00::/40`
+    /// announcement.
+    const RAW_UPDATE: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF005A02000000434001010040020E02030000E3BB0000C33D00061162800E2B00020120200107F8002400000000000000000082FE80000000000000768EF8FFFEA6B2C400282602FD9E0F";
+
+    #[test]
+    fn decode_raw_update_recovers_ipv6_next_hop() {
+        let announced: Vec<NetworkPrefix> = vec!["2602:fd9e:f00::/40".parse().unwrap()];
+        let attrs = decode_raw_update(RAW_UPDATE, &announced).expect(
+            "a correctly-offset MP_REACH_NLRI parse must agree with the JSON's own prefixes",
+        );
+        assert_eq!(
+            attrs.next_hop_v6,
+            Some("2001:7f8:24::82".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn decode_raw_update_rejects_mismatched_prefixes() {
+        let announced: Vec<NetworkPrefix> = vec!["192.0.2.0/24".parse().unwrap()];
+        assert!(matches!(
+            decode_raw_update(RAW_UPDATE, &announced),
+            Err(ParserRisliveError::IncorrectJson(_))
+        ));
+    }
+}
@@ -0,0 +1,44 @@
+//! Streaming transports for RIS Live.
+//!
+//! RIS Live is reachable either interactively, over the WebSocket endpoint subscribed to with
+//! [crate::messages::RisSubscribe], or as a non-interactive "firehose": a plain HTTPS
+//! endpoint that pushes the full, unfiltered stream as chunked, newline-delimited JSON. The
+//! firehose has no subscription handshake - every line on the wire is a `ris_message`/
+//! `ris_error` object, identical in shape to what the WebSocket emits.
+
+use std::io::{BufRead, BufReader};
+
+/// Base URL for the RIS Live firehose: the full stream over plain HTTPS.
+pub const RIS_LIVE_FIREHOSE_URL: &str = "https://ris-live.ripe.net/v1/stream/?format=json";
+
+/// Opens the RIS Live firehose and invokes `on_line` with each complete line as it arrives.
+///
+/// The firehose is a long-lived chunked HTTP response, so the body is read incrementally
+/// through a [BufReader] rather than buffered in full; partial lines spanning chunk
+/// boundaries are handled transparently by [BufRead::read_line].
+pub fn stream_firehose<F>(client_name: &str, mut on_line: F) -> Result<(), reqwest::Error>
+where
+    F: FnMut(&str),
+{
+    let url = format!("{}&client={}", RIS_LIVE_FIREHOSE_URL, client_name);
+    let resp = reqwest::blocking::get(url)?.error_for_status()?;
+    let mut reader = BufReader::new(resp);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = match reader.read_line(&mut line) {
+            Ok(n) => n,
+            Err(_) => break,
+        };
+        if read == 0 {
+            // connection closed
+            break;
+        }
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if trimmed.is_empty() {
+            continue;
+        }
+        on_line(trimmed);
+    }
+    Ok(())
+}